@@ -1,20 +1,8 @@
 use crate::BishopCliError;
-use sha2::{Digest, Sha256};
 use std::io::{self, ErrorKind, Read};
 
 fn _raise_io<S: Into<String>, T>(m: S) -> io::Result<T> {
-  Err(io::Error::new(
-    ErrorKind::Other,
-    BishopCliError::Other { msg: m.into() },
-  ))
-}
-
-pub type HashArray = [u8; 32];
-
-pub fn hash_input<R: Read>(r: &mut R) -> io::Result<HashArray> {
-  let mut h = Sha256::new();
-  io::copy(r, &mut h)?;
-  Ok(h.result().into())
+  Err(io::Error::other(BishopCliError::Other { msg: m.into() }))
 }
 
 const HEX_BUF_SIZE: usize = 128;
@@ -40,7 +28,7 @@ impl<R: Read> Read for HexInput<R> {
     let mut rd_len = loop {
       match self.b.read(&mut h_buf[..h_len]) {
         Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
-        Err(e) => return Err(e.into()),
+        Err(e) => return Err(e),
         Ok(0) => return Ok(0),
         Ok(x) => break x,
       };
@@ -88,9 +76,7 @@ impl<R: Read> Read for HexInput<R> {
       panic!("small out buf");
     }
 
-    for i in 0..dec_ln {
-      buf[i] = dec[i]
-    }
+    buf[..dec_ln].copy_from_slice(&dec);
 
     Ok(dec_ln)
   }
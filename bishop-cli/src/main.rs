@@ -3,11 +3,10 @@ mod input_data;
 #[macro_use]
 extern crate custom_error;
 
-use bishop::{errors::Error as BishopError, bishop_art::DEFAULT_CHARS, *};
+use bishop::{errors::Error as BishopError, color::{ColorDepth, ColorOptions}, ssh::{cert, known_hosts}, *};
 use std::fs::File;
-use std::io::{self, BufReader, Read};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
 use std::path::PathBuf;
-use clap;
 use clap::Parser as _;
 
 use input_data::*;
@@ -27,6 +26,185 @@ enum InputType {
     Hash
 }
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+enum Format {
+  Text,
+  Svg,
+  Png,
+  Ppm,
+  Pgm,
+  Gif,
+  Cast,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+enum Moves {
+  Stairs,
+  Diagonal,
+  King,
+  Knight,
+  Hex,
+  Nibble,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+enum Order {
+  Lsb,
+  Msb,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Frame {
+  Ascii,
+  Single,
+  Double,
+  Rounded,
+  Heavy,
+  None,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum CharsetName {
+  Openssh,
+  Shading,
+  Dots,
+  AsciiSafe,
+  Digits,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Overflow {
+  Cut,
+  Ellipsis,
+  Wrap,
+}
+
+/// Place of `--label` text
+#[derive(Debug, Clone, Copy)]
+enum LabelPos {
+  Top(bishop_art::Align),
+  Bottom(bishop_art::Align),
+  Left,
+  Right,
+}
+
+fn parse_label(s: &str) -> Result<(LabelPos, String), String> {
+  use bishop_art::Align::*;
+
+  let (pos, text) = s.split_once('=').ok_or("expected WHERE=TEXT")?;
+  let pos = match pos.to_ascii_lowercase().as_str() {
+    "top-left" => LabelPos::Top(Left),
+    "top" => LabelPos::Top(Center),
+    "top-right" => LabelPos::Top(Right),
+    "bottom-left" => LabelPos::Bottom(Left),
+    "bottom" => LabelPos::Bottom(Center),
+    "bottom-right" => LabelPos::Bottom(Right),
+    "left" => LabelPos::Left,
+    "right" => LabelPos::Right,
+    _ => return Err(format!(
+      "unknown place `{}`, expected top, bottom (both with optional -left or -right), left or right",
+      pos
+    )),
+  };
+  Ok((pos, text.to_string()))
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum ColorWhen {
+  Auto,
+  Always,
+  Never,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum HashAlg {
+  Sha256,
+  Sha512,
+  Sha1,
+  Md5,
+  Blake2b,
+  Blake3,
+}
+
+impl From<HashAlg> for hashed::Algorithm {
+  fn from(h: HashAlg) -> Self {
+    match h {
+      HashAlg::Sha256 => hashed::Algorithm::Sha256,
+      HashAlg::Sha512 => hashed::Algorithm::Sha512,
+      HashAlg::Sha1 => hashed::Algorithm::Sha1,
+      HashAlg::Md5 => hashed::Algorithm::Md5,
+      HashAlg::Blake2b => hashed::Algorithm::Blake2b,
+      HashAlg::Blake3 => hashed::Algorithm::Blake3,
+    }
+  }
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum SshHash {
+  Sha256,
+  Md5,
+}
+
+impl From<SshHash> for ssh::FingerprintHash {
+  fn from(h: SshHash) -> Self {
+    match h {
+      SshHash::Sha256 => ssh::FingerprintHash::Sha256,
+      SshHash::Md5 => ssh::FingerprintHash::Md5,
+    }
+  }
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+  /// Visualize OpenSSH public keys and certificates from `.pub` or `authorized_keys` file
+  Ssh {
+    /// Key file, `-` for stdin
+    file: PathBuf,
+
+    /// Fingerprint hash algorithm
+    #[arg(short = 'E', ignore_case = true, value_enum, default_value = "sha256")]
+    hash: SshHash,
+  },
+
+  /// Visualize host keys from known_hosts file
+  KnownHosts {
+    /// known_hosts file, `-` for stdin [default: ~/.ssh/known_hosts]
+    file: Option<PathBuf>,
+
+    /// Show only entries for this host, including hashed ones
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Fingerprint hash algorithm
+    #[arg(short = 'E', ignore_case = true, value_enum, default_value = "sha256")]
+    hash: SshHash,
+  },
+
+  /// Visualize identities loaded into ssh-agent ($SSH_AUTH_SOCK)
+  #[cfg(unix)]
+  Agent {
+    /// Fingerprint hash algorithm
+    #[arg(short = 'E', ignore_case = true, value_enum, default_value = "sha256")]
+    hash: SshHash,
+  },
+
+  /// Visualize OpenPGP public keys and subkeys from armored or binary key file
+  Pgp {
+    /// Key file, `-` for stdin
+    file: PathBuf,
+  },
+
+  /// Visualize X.509 certificates and CSRs from PEM bundle or DER file
+  X509 {
+    /// Certificate file, `-` for stdin
+    file: PathBuf,
+
+    /// Visualize SubjectPublicKeyInfo SHA-256 (public key pin) instead of whole certificate
+    #[arg(long)]
+    spki: bool,
+  },
+}
+
 #[derive(Debug)]
 enum Input<'a> {
   StdIn,
@@ -40,7 +218,7 @@ fn _raise<R, S: Into<String>>(m: S) -> Result<R, BishopCliError> {
 
 /// Visualizes keys and hashes using OpenSSH's Drunken Bishop algorithm
 #[derive(clap::Parser, Debug)]
-#[command(name = "bishop-cli")]
+#[command(name = "bishop-cli", disable_help_flag = true)]
 struct Opts {
   /// Input file
   #[arg(short, name = "file", display_order = 100)]
@@ -60,7 +238,7 @@ struct Opts {
     Input type for -i
  bin  - Treat as binary data (default)
  hex  - Treat as HEX data
- hash - Hash input file as binary and then visualize hash (sha256)
+ hash - Hash input file as binary and then visualize hash (see -A)
         Use this for large inputs
  "
   )]
@@ -74,53 +252,207 @@ struct Opts {
   #[arg(short = 'X', display_order = 202)]
   hex_input: bool,
 
+  /// Hash algorithm for -I hash, implies -I hash [default: sha256]
+  #[arg(short = 'A', long, ignore_case = true, value_enum, display_order = 203)]
+  algorithm: Option<HashAlg>,
+
   /// HEX input, should have even length
   #[arg(name = "hex")]
   hex: Option<String>,
 
   /// Don't echo hex input
-  #[arg(short, long, display_order = 0)]
+  #[arg(short, long, global = true, display_order = 0)]
   quiet: bool,
 
   /// Custom char list: '[bg][char]...[start][end]'
-  #[arg(long, display_order = 200)]
-  chars: Option<String>,
+  #[arg(long, conflicts_with = "charset", global = true, display_order = 400)]
+  chars: Option<bishop_art::Charset>,
+
+  /// Named char list: `ascii-safe` has no confusable chars, `digits` shows visit counts
+  #[arg(
+    long,
+    value_name = "NAME",
+    ignore_case = true,
+    value_enum,
+    default_value = "openssh",
+    global = true,
+    display_order = 401
+  )]
+  charset: CharsetName,
+
+  /// Char for start position, replaces the one of --chars or --charset
+  #[arg(long, value_name = "CHAR", global = true, display_order = 402)]
+  start_char: Option<char>,
+
+  /// Char for end position, replaces the one of --chars or --charset
+  #[arg(long, value_name = "CHAR", global = true, display_order = 403)]
+  end_char: Option<char>,
 
   /// Field width
-  #[arg(short, long, default_value = "17", display_order = 301)]
+  #[arg(short, long, default_value = "17", global = true, display_order = 301)]
   width: usize,
 
   /// Field height
-  #[arg(short, long, default_value = "9", display_order = 302)]
+  #[arg(short, long, default_value = "9", global = true, display_order = 302)]
   height: usize,
 
+  /// Move rule: axis moves (1 bit), OpenSSH diagonal moves (2 bits), king moves,
+  /// knight jumps, hexagonal grid (3 bits) or nibble jumps (4 bits)
+  #[arg(
+    long,
+    value_name = "RULE",
+    ignore_case = true,
+    value_enum,
+    default_value = "diagonal",
+    display_order = 303
+  )]
+  moves: Moves,
+
+  /// Take moves from each byte starting from least or most significant bits
+  #[arg(
+    long,
+    ignore_case = true,
+    value_enum,
+    default_value = "lsb",
+    display_order = 304
+  )]
+  bit_order: Order,
+
+  /// Reverse input bytes in chunks of N bytes before the walk
+  #[arg(
+    long,
+    value_name = "N",
+    default_value = "1",
+    value_parser = clap::value_parser!(u64).range(1..),
+    display_order = 305
+  )]
+  reverse_chunk: u64,
+
   /// Top frame text
-  #[arg(short, long, display_order = 401)]
+  #[arg(short, long, global = true, display_order = 500)]
   top: Option<String>,
 
   /// Bottom frame text
-  #[arg(short, long, display_order = 402)]
+  #[arg(short, long, global = true, display_order = 501)]
   bot: Option<String>,
+
+  /// More frame text, repeatable: WHERE is top, bottom (both with optional
+  /// -left or -right) or left and right for captions down the sides
+  #[arg(
+    long,
+    value_name = "WHERE=TEXT",
+    value_parser = parse_label,
+    global = true,
+    display_order = 502
+  )]
+  label: Vec<(LabelPos, String)>,
+
+  /// Colorize output: `auto` colors only terminal output and respects $NO_COLOR
+  #[arg(
+    long,
+    value_name = "WHEN",
+    ignore_case = true,
+    value_enum,
+    default_value = "auto",
+    global = true,
+    display_order = 503
+  )]
+  color: ColorWhen,
+
+  /// Frame style: `ascii` is the OpenSSH look, `none` draws only the field and texts
+  #[arg(
+    long,
+    value_name = "STYLE",
+    ignore_case = true,
+    value_enum,
+    default_value = "ascii",
+    global = true,
+    display_order = 504
+  )]
+  frame: Frame,
+
+  /// Frame texts that don't fit: `wrap` moves them to lines above or below the frame
+  #[arg(
+    long,
+    value_name = "POLICY",
+    ignore_case = true,
+    value_enum,
+    default_value = "cut",
+    global = true,
+    display_order = 505
+  )]
+  overflow: Overflow,
+
+  /// Output format
+  #[arg(long, ignore_case = true, value_enum, default_value = "text", display_order = 600)]
+  format: Format,
+
+  /// Draw field as filled squares instead of chars (svg, png, ppm, pgm, gif)
+  #[arg(long, display_order = 601)]
+  squares: bool,
+
+  /// Size of field cell in pixels (svg, png, ppm, pgm, gif)
+  #[arg(
+    long,
+    value_name = "PX",
+    value_parser = clap::value_parser!(u32).range(1..=256),
+    display_order = 602
+  )]
+  cell_size: Option<u32>,
+
+  /// Bytes of input drawn per animation frame (gif)
+  #[arg(
+    long,
+    value_name = "N",
+    default_value = "1",
+    value_parser = clap::value_parser!(u64).range(1..),
+    display_order = 603
+  )]
+  step_bytes: u64,
+
+  /// Animate every move instead of every byte (--animate, cast)
+  #[arg(long, display_order = 604)]
+  per_move: bool,
+
+  /// Delay between animation frames in milliseconds (--animate, gif, cast)
+  #[arg(long, value_name = "MS", default_value = "100", display_order = 605)]
+  delay: u64,
+
+  /// Redraw the field in terminal as input is consumed
+  #[arg(long, display_order = 606)]
+  animate: bool,
+
+  /// Write drawing to file instead of stdout, required for png, ppm, pgm and gif
+  #[arg(short, long, value_name = "FILE", display_order = 607)]
+  output: Option<PathBuf>,
+
+  /// Print help
+  #[arg(long, global = true, action = clap::ArgAction::Help, display_order = 1000)]
+  help: Option<bool>,
+
+  #[command(subcommand)]
+  command: Option<Command>,
 }
 
 fn input_echo(h: &impl AsRef<str>) {
   println!("Fingerprint of:\n{}\n", h.as_ref());
 }
 
-fn art_from_read<R: Read>(
+fn art_from_read<R: Read, W: Write + ?Sized>(
   mut r: R,
   t: &InputType,
-  art: &mut BishopArt,
+  alg: HashAlg,
+  art: &mut W,
   quiet: bool,
 ) -> io::Result<u64> {
   match t {
     Bin => io::copy(&mut r, art),
     Hash => {
-      let a = hash_input(&mut r)?;
+      let a = hashed::Algorithm::from(alg).digest(&mut r)?;
       if !quiet {
-        input_echo(&hex::encode(a));
+        input_echo(&hex::encode(&a));
       }
-      io::copy(&mut a.as_ref(), art)
+      io::copy(&mut a.as_slice(), art)
     }
     Hex => {
       let mut i = HexInput::new(r);
@@ -129,9 +461,192 @@ fn art_from_read<R: Read>(
   }
 }
 
-fn str_opt<'a>(s: &'a Option<String>, d: &'static str) -> &'a str {
+fn open_buf(file: &PathBuf) -> io::Result<Box<dyn BufRead>> {
+  Ok(if file.as_os_str() == "-" {
+    Box::new(io::stdin().lock())
+  } else {
+    Box::new(BufReader::new(File::open(file)?))
+  })
+}
+
+fn ssh_keys(
+  file: &PathBuf,
+  o: &Opts,
+  draw_opts: &DrawingOptions,
+  hash: SshHash,
+) -> Result<(), BishopCliError> {
+  let new_art = || BishopArt::with_size(o.width, o.height);
+
+  let skip = |line: usize, error: BishopError| {
+    eprintln!("Skipped {}", ssh::SkippedLine { line, error })
+  };
+
+  for (i, line) in open_buf(file)?.lines().enumerate() {
+    let line = line?;
+    let l = line.trim();
+    if l.is_empty() || l.starts_with('#') {
+      continue;
+    }
+
+    if cert::is_cert(l) {
+      let c = match cert::parse_line(l) {
+        Ok(c) => c,
+        Err(e) => {
+          skip(i + 1, e);
+          continue;
+        }
+      };
+      if !o.quiet {
+        println!("{}", c.describe(hash.into()));
+      }
+      let arts = c.arts_with(hash.into(), new_art()?, new_art()?);
+      print!("{}", cert::Certificate::draw_arts(&arts, draw_opts));
+    } else {
+      let key = match ssh::parse_line(l) {
+        Ok(k) => k,
+        Err(e) => {
+          skip(i + 1, e);
+          continue;
+        }
+      };
+      let key_art = key.art_with(hash.into(), new_art()?);
+      if !o.quiet {
+        println!("{}", key_art);
+      }
+      print!("{}", key_art.draw_with_opts(draw_opts));
+    }
+  }
+
+  Ok(())
+}
+
+fn known_hosts(
+  file: &Option<PathBuf>,
+  host: &Option<String>,
+  o: &Opts,
+  draw_opts: &DrawingOptions,
+  hash: SshHash,
+) -> Result<(), BishopCliError> {
+  let file = match (file, std::env::var_os("HOME")) {
+    (Some(f), _) => f.clone(),
+    (None, Some(home)) => PathBuf::from(home).join(".ssh/known_hosts"),
+    (None, None) => _raise("No known_hosts file specified and $HOME is not set")?,
+  };
+
+  let (entries, skipped) = known_hosts::parse(open_buf(&file)?)?;
+  for s in skipped {
+    eprintln!("Skipped {}", s);
+  }
+
+  for e in entries {
+    let label = match host {
+      Some(h) if e.matches(h) => e.label_as(h),
+      Some(_) => continue,
+      None => e.label(),
+    };
+
+    let art = BishopArt::with_size(o.width, o.height)?;
+    let mut key_art = e.key.art_with(hash.into(), art);
+    key_art.comment = label.clone();
+
+    if !o.quiet {
+      println!("{}", key_art);
+    }
+
+    let opts = DrawingOptions {
+      top_text: str_opt(&o.top, &label).to_string(),
+      ..draw_opts.clone()
+    };
+    print!("{}", key_art.draw_with_opts(&opts));
+  }
+
+  Ok(())
+}
+
+#[cfg(unix)]
+fn agent_keys(o: &Opts, draw_opts: &DrawingOptions, hash: SshHash) -> Result<(), BishopCliError> {
+  let ids = ssh::agent::AgentClient::from_env()?.identities()?;
+
+  for (i, id) in ids.iter().enumerate() {
+    let art = BishopArt::with_size(o.width, o.height)?;
+    let key_art = match id.art_with(hash.into(), art) {
+      Ok(a) => a,
+      Err(error) => {
+        eprintln!("Skipped {}", ssh::SkippedLine { line: i + 1, error });
+        continue;
+      }
+    };
+
+    if !o.quiet {
+      println!("{}", key_art);
+    }
+
+    let opts = DrawingOptions {
+      top_text: str_opt(&o.top, &id.comment).to_string(),
+      ..draw_opts.clone()
+    };
+    print!("{}", key_art.draw_with_opts(&opts));
+  }
+
+  Ok(())
+}
+
+fn pgp_keys(file: &PathBuf, o: &Opts, draw_opts: &DrawingOptions) -> Result<(), BishopCliError> {
+  let mut data = vec![];
+  open_buf(file)?.read_to_end(&mut data)?;
+
+  for key in pgp::parse(&data)? {
+    for pgp_art in key.arts_with(&BishopArt::with_size(o.width, o.height)?) {
+      if !o.quiet {
+        println!("{}", pgp_art);
+      }
+      print!("{}", pgp_art.draw_with_opts(draw_opts));
+    }
+  }
+
+  Ok(())
+}
+
+fn x509_certs(
+  file: &PathBuf,
+  spki: bool,
+  o: &Opts,
+  draw_opts: &DrawingOptions,
+) -> Result<(), BishopCliError> {
+  let mut data = vec![];
+  open_buf(file)?.read_to_end(&mut data)?;
+
+  let digest = if spki {
+    x509::DigestKind::Spki
+  } else {
+    x509::DigestKind::Document
+  };
+
+  for (i, doc) in x509::parse(&data)?.iter().enumerate() {
+    let cert_art = doc.art_with(digest, BishopArt::with_size(o.width, o.height)?);
+    if !o.quiet {
+      println!("#{} {}", i, cert_art);
+    }
+    print!("{}", cert_art.draw_with_opts(draw_opts));
+  }
+
+  Ok(())
+}
+
+fn use_color(when: ColorWhen) -> bool {
+  match when {
+    ColorWhen::Always => true,
+    ColorWhen::Never => false,
+    ColorWhen::Auto => {
+      let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+      !no_color && io::stdout().is_terminal()
+    }
+  }
+}
+
+fn str_opt<'a>(s: &'a Option<String>, d: &'a str) -> &'a str {
   if let Some(s) = s {
-    &s
+    s
   } else {
     d
   }
@@ -140,13 +655,104 @@ fn str_opt<'a>(s: &'a Option<String>, d: &'static str) -> &'a str {
 fn main_() -> Result<(), BishopCliError> {
   let o = Opts::parse();
 
+  let labels = |top: bool| -> Vec<_> {
+    o.label.iter().filter_map(|(pos, text)| match (pos, top) {
+      (LabelPos::Top(a), true) | (LabelPos::Bottom(a), false) => Some(bishop_art::Label::new(text.as_str(), *a)),
+      _ => None,
+    }).collect()
+  };
+  // last caption for each side wins
+  let caption = |left: bool| {
+    o.label.iter().rev().find_map(|(pos, text)| match (pos, left) {
+      (LabelPos::Left, true) | (LabelPos::Right, false) => Some(text.clone()),
+      _ => None,
+    }).unwrap_or_default()
+  };
+
+  let mut chars = o.chars.clone().unwrap_or_else(|| match o.charset {
+    CharsetName::Openssh => bishop_art::Charset::openssh(),
+    CharsetName::Shading => bishop_art::Charset::shading(),
+    CharsetName::Dots => bishop_art::Charset::dots(),
+    CharsetName::AsciiSafe => bishop_art::Charset::ascii_safe(),
+    CharsetName::Digits => bishop_art::Charset::digits(),
+  });
+  if let Some(c) = o.start_char {
+    chars = chars.with_start(c)?;
+  }
+  if let Some(c) = o.end_char {
+    chars = chars.with_end(c)?;
+  }
+
   let draw_opts = DrawingOptions {
-    chars: str_opt(&o.chars, DEFAULT_CHARS).chars().collect(),
+    chars,
     top_text: str_opt(&o.top, "").to_string(),
     bottom_text: str_opt(&o.bot, "").to_string(),
+    top_labels: labels(true),
+    bottom_labels: labels(false),
+    left_caption: caption(true),
+    right_caption: caption(false),
+    frame: bishop_art::FrameStyle {
+      overflow: match o.overflow {
+        Overflow::Cut => bishop_art::Overflow::Cut,
+        Overflow::Ellipsis => bishop_art::Overflow::Ellipsis,
+        Overflow::Wrap => bishop_art::Overflow::Wrap,
+      },
+      ..match o.frame {
+        Frame::Ascii => bishop_art::FrameStyle::ascii(),
+        Frame::Single => bishop_art::FrameStyle::single(),
+        Frame::Double => bishop_art::FrameStyle::double(),
+        Frame::Rounded => bishop_art::FrameStyle::rounded(),
+        Frame::Heavy => bishop_art::FrameStyle::heavy(),
+        Frame::None => bishop_art::FrameStyle::none(),
+      }
+    },
+    color: use_color(o.color).then(|| ColorOptions::with_depth(ColorDepth::from_env())),
   };
 
-  let mut art = BishopArt::with_size(o.width, o.height)?;
+  if o.command.is_some() && (o.format != Format::Text || o.output.is_some()) {
+    _raise("--format and --output are supported only for data input")?;
+  }
+  let walk_opts = o.moves != Moves::Diagonal || o.bit_order != Order::Lsb || o.reverse_chunk != 1;
+  if o.command.is_some() && walk_opts {
+    _raise("--moves, --bit-order and --reverse-chunk are supported only for data input")?;
+  }
+  let binary = matches!(o.format, Format::Png | Format::Ppm | Format::Pgm | Format::Gif);
+  if binary && o.output.is_none() {
+    _raise("--output is required for png, ppm, pgm and gif")?;
+  }
+  if o.animate && (o.format != Format::Text || o.output.is_some()) {
+    _raise("--animate draws only to terminal, use --format cast to record it")?;
+  }
+
+  match &o.command {
+    Some(Command::Ssh { file, hash }) => return ssh_keys(file, &o, &draw_opts, *hash),
+    Some(Command::KnownHosts { file, host, hash }) => {
+      return known_hosts(file, host, &o, &draw_opts, *hash)
+    }
+    #[cfg(unix)]
+    Some(Command::Agent { hash }) => return agent_keys(&o, &draw_opts, *hash),
+    Some(Command::Pgp { file }) => return pgp_keys(file, &o, &draw_opts),
+    Some(Command::X509 { file, spki }) => return x509_certs(file, *spki, &o, &draw_opts),
+    None => (),
+  }
+
+  let decoding = bishop_art::Decoding {
+    order: match o.bit_order {
+      Order::Lsb => bishop_art::BitOrder::Lsb,
+      Order::Msb => bishop_art::BitOrder::Msb,
+    },
+    reverse_chunk: o.reverse_chunk as usize,
+  };
+  let builder = BishopArt::builder(o.width, o.height).decoding(decoding);
+  let mut art = match o.moves {
+    Moves::Stairs => builder.move_rule(bishop_art::Stairs),
+    Moves::Diagonal => builder,
+    Moves::King => builder.move_rule(bishop_art::King),
+    Moves::Knight => builder.move_rule(bishop_art::Knight),
+    Moves::Hex => builder.move_rule(bishop_art::Hex),
+    Moves::Nibble => builder.move_rule(bishop_art::Nibble),
+  }
+  .build()?;
 
   let mut input_t_set = o.input_type.is_some();
   let mut input_t = *(o.input_type.as_ref().unwrap_or(&Bin));
@@ -167,6 +773,15 @@ fn main_() -> Result<(), BishopCliError> {
       input_t_set = true;
       input_t = sh_input_type;
   }
+  match (o.algorithm, input_t) {
+    (None, _) | (Some(_), Hash) => (),
+    (Some(_), _) if !input_t_set => {
+      input_t_set = true;
+      input_t = Hash;
+    }
+    _ => _raise("Hash algorithm can only be used with -I hash")?,
+  }
+  let alg = o.algorithm.unwrap_or(HashAlg::Sha256);
 
 
   let input_f = match (o.input_stdin, &o.input, &o.hex) {
@@ -183,28 +798,122 @@ fn main_() -> Result<(), BishopCliError> {
     }
   };
 
+  let raster_opts = || {
+    let mode = if o.squares {
+      raster::RasterMode::Squares
+    } else {
+      raster::RasterMode::Glyphs
+    };
+    let d = raster::RasterOptions::default();
+    raster::RasterOptions {
+      mode,
+      cell_size: o.cell_size.unwrap_or(d.cell_size),
+      ..d
+    }
+  };
+
+  let gif_opts = gif::GifOptions {
+    step_bytes: o.step_bytes as usize,
+    delay: (o.delay / 10).min(u64::from(u16::MAX)) as u16,
+    raster: raster_opts(),
+    ..Default::default()
+  };
+
+  // reject images too large to draw before reading the input
+  match o.format {
+    Format::Png | Format::Ppm | Format::Pgm => {
+      raster::size(&art.clone().result(), &draw_opts, &raster_opts())?;
+    }
+    Format::Gif => {
+      gif::size(&art.clone().result(), &draw_opts, &gif_opts)?;
+    }
+    _ => (),
+  }
+
+  // animation needs the whole walk, so data is collected instead of drawn
+  let mut walk_data = vec![];
+  let walk = o.animate || matches!(o.format, Format::Gif | Format::Cast);
+  let sink: &mut dyn Write = if walk {
+    &mut walk_data
+  } else {
+    &mut art
+  };
+
   match input_f {
     Input::StdIn => {
       let bf = io::stdin();
-      art_from_read(bf.lock(), &input_t, &mut art, quiet)?;
+      art_from_read(bf.lock(), &input_t, alg, sink, quiet)?;
     }
     Input::File(i) => {
       let f = File::open(i)?;
       let bf = BufReader::new(f);
-      art_from_read(bf, &input_t, &mut art, quiet)?;
+      art_from_read(bf, &input_t, alg, sink, quiet)?;
     }
     Input::Hex(h) => {
       if !quiet {
         input_echo(&h);
       }
       let d = hex::decode(h)?;
-      art.input(d);
+      sink.write_all(&d)?;
     }
   };
 
-  let res = art.draw_with_opts(&draw_opts);
-  print!("{}", res);
+  let delay = std::time::Duration::from_millis(o.delay);
+  let step = if o.per_move {
+    animate::Granularity::Move
+  } else {
+    animate::Granularity::Byte
+  };
+
+  if o.animate {
+    let frames = animate::frames(art, &walk_data, step);
+    animate::play(&frames, &draw_opts, delay, io::stdout())?;
+    return Ok(());
+  }
 
+  let mut out: Vec<u8> = vec![];
+  match o.format {
+    Format::Text => out.extend(art.draw_with_opts(&draw_opts).bytes()),
+    Format::Svg => {
+      let mode = if o.squares {
+        svg::SvgMode::Squares
+      } else {
+        svg::SvgMode::Glyphs
+      };
+      let d = svg::SvgOptions::default();
+      let svg_opts = svg::SvgOptions {
+        mode,
+        cell_size: o.cell_size.unwrap_or(d.cell_size),
+        ..d
+      };
+      out.extend(svg::draw(&art.result(), &draw_opts, &svg_opts).bytes())
+    }
+    Format::Png | Format::Ppm | Format::Pgm => {
+      let img = raster::draw(&art.result(), &draw_opts, &raster_opts())?;
+      match o.format {
+        Format::Png => img.write_png(&mut out)?,
+        Format::Ppm => img.write_ppm(&mut out)?,
+        _ => img.write_pgm(&mut out)?,
+      }
+    }
+    Format::Gif => {
+      out = gif::draw(art, &walk_data, &draw_opts, &gif_opts)?;
+    }
+    Format::Cast => {
+      let frames = animate::frames(art, &walk_data, step);
+      animate::asciicast(&frames, &draw_opts, delay, &mut out)?;
+    }
+  };
+  write_output(&o.output, &out)?;
+
+  Ok(())
+}
+
+fn write_output(path: &Option<PathBuf>, data: &[u8]) -> Result<(), BishopCliError> {
+  match path {
+    Some(p) if p.as_os_str() != "-" => std::fs::write(p, data)?,
+    _ => io::stdout().write_all(data)?,
+  }
   Ok(())
 }
 
@@ -219,8 +928,8 @@ fn main() {
 mod tests {
   use super::*;
 
+  use bishop::hashed::{Digest, Sha256};
   use rand::prelude::*;
-  use sha2::{Digest, Sha256};
 
   use std::error::Error as StdError;
   type StdResult = Result<(), Box<dyn StdError>>;
@@ -232,11 +941,11 @@ mod tests {
     let mut data = [0u8; 64];
     thread_rng().fill_bytes(&mut data);
 
-    let ref_hash: [u8; 32] = Sha256::new().chain(data.as_ref()).result().into();
+    let ref_hash = Sha256::digest(data);
     let ref_art = BishopArt::new().chain(ref_hash).draw();
 
     let mut art_inst = BishopArt::new();
-    art_from_read(data.as_ref(), &InputType::Hash, &mut art_inst, QUIET)?;
+    art_from_read(data.as_ref(), &InputType::Hash, HashAlg::Sha256, &mut art_inst, QUIET)?;
     let chk_art = art_inst.draw();
 
     assert_eq!(ref_art, chk_art);
@@ -252,7 +961,7 @@ mod tests {
     let hex = hex::encode(data.as_ref());
 
     let mut art_inst = BishopArt::new();
-    art_from_read(hex.as_bytes(), &InputType::Hex, &mut art_inst, QUIET)?;
+    art_from_read(hex.as_bytes(), &InputType::Hex, HashAlg::Sha256, &mut art_inst, QUIET)?;
     let chk_art = art_inst.draw();
 
     assert_eq!(ref_art, chk_art);
@@ -267,10 +976,24 @@ mod tests {
     let ref_art = BishopArt::new().chain(data).draw();
 
     let mut art_inst = BishopArt::new();
-    art_from_read(data.as_ref(), &InputType::Bin, &mut art_inst, QUIET)?;
+    art_from_read(data.as_ref(), &InputType::Bin, HashAlg::Sha256, &mut art_inst, QUIET)?;
     let chk_art = art_inst.draw();
 
     assert_eq!(ref_art, chk_art);
     Ok(())
   }
+
+  #[test]
+  fn test_subcommand_options() {
+    let drawing = [
+      "-q", "-w", "21", "-h", "7", "--charset", "digits", "-t", "top", "--label", "left=l",
+    ];
+    let commands = [&["ssh", "key.pub"][..], &["known-hosts"], &["pgp", "key.asc"], &["x509", "a.pem"]];
+    for cmd in commands {
+      let args = [&["bishop"][..], cmd, &drawing].concat();
+      let o = Opts::try_parse_from(args).unwrap();
+      assert!(o.quiet && o.command.is_some());
+      assert_eq!((o.width, o.height, o.label.len()), (21, 7, 1));
+    }
+  }
 }
@@ -0,0 +1,188 @@
+use crate::bishop_art::{line_width, BishopArt, BishopResult, DrawingOptions};
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// How much of the walk is done between animation frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+  /// One byte of input, moves per byte depend on [`MoveRule::bits()`]
+  ///
+  /// [`MoveRule::bits()`]: ../bishop_art/trait.MoveRule.html#tymethod.bits
+  Byte,
+  /// One move
+  Move,
+}
+
+/// Push `data` to field and collect fields after each step
+///
+/// First field is the initial one, the last one is the finished
+/// field with start and end positions (same as [`BishopArt::result()`]),
+/// fields in between have no end position, so each field is
+/// a prefix of the walk drawn by the next one
+///
+/// [`BishopArt::result()`]: ../bishop_art/struct.BishopArt.html#method.result
+pub fn frames(art: BishopArt, data: &[u8], step: Granularity) -> Vec<BishopResult> {
+  match step {
+    Granularity::Byte => walk(art, |art, f| {
+      for &byte in data {
+        art.input([byte]);
+        f(art);
+      }
+    }),
+    Granularity::Move => walk(art, |art, f| art.input_with(data, |a| f(a))),
+  }
+}
+
+/// Snapshots of the initial field and of the field after each step
+/// reported by `steps`, the last one is replaced with the finished field
+pub(crate) fn walk<F>(mut art: BishopArt, steps: F) -> Vec<BishopResult>
+where
+  F: FnOnce(&mut BishopArt, &mut dyn FnMut(&BishopArt)),
+{
+  let mut frames = vec![art.snapshot()];
+  steps(&mut art, &mut |a| frames.push(a.snapshot()));
+  frames.pop();
+  frames.push(art.result());
+  frames
+}
+
+/// Drawn frames, each but the first prefixed with cursor-up escape
+/// to draw over the previous one
+fn redraws(frames: &[BishopResult], o: &DrawingOptions) -> Vec<String> {
+  let mut lines = 0;
+  frames
+    .iter()
+    .map(|f| {
+      let s = f.draw_with_opts(o);
+      let up = lines;
+      lines = s.lines().count();
+      if up == 0 {
+        s
+      } else {
+        format!("\x1b[{}A{}", up, s)
+      }
+    })
+    .collect()
+}
+
+/// Play frames in terminal, redrawing the field in place
+pub fn play<W: Write>(
+  frames: &[BishopResult],
+  o: &DrawingOptions,
+  delay: Duration,
+  mut w: W,
+) -> io::Result<()> {
+  for (i, s) in redraws(frames, o).iter().enumerate() {
+    if i > 0 {
+      thread::sleep(delay);
+    }
+    w.write_all(s.as_bytes())?;
+    w.flush()?;
+  }
+  Ok(())
+}
+
+fn json_string(s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+/// Record frames as asciinema v2 `.cast` file
+pub fn asciicast<W: Write>(
+  frames: &[BishopResult],
+  o: &DrawingOptions,
+  delay: Duration,
+  mut w: W,
+) -> io::Result<()> {
+  let redraws = redraws(frames, o);
+  // first frame has no escape, one more row for the cursor
+  let (cols, rows) = match redraws.first() {
+    Some(s) => (
+      s.lines().map(line_width).max().unwrap_or(0),
+      s.lines().count() + 1,
+    ),
+    None => (0, 0),
+  };
+  writeln!(
+    w,
+    "{{\"version\": 2, \"width\": {}, \"height\": {}}}",
+    cols, rows
+  )?;
+
+  for (i, s) in redraws.iter().enumerate() {
+    // terminal in raw mode needs carriage returns
+    let s = s.replace('\n', "\r\n");
+    let t = delay.as_secs_f64() * i as f64;
+    writeln!(w, "[{:.6}, \"o\", {}]", t, json_string(&s))?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bishop_art::Decoding;
+
+  #[test]
+  fn test_frames() {
+    let data = b"anim";
+    let bytes = frames(BishopArt::new(), data, Granularity::Byte);
+    let moves = frames(BishopArt::new(), data, Granularity::Move);
+    assert_eq!((bytes.len(), moves.len()), (5, 17));
+
+    let last = BishopArt::new().chain(data).result();
+    assert_eq!(bytes[4].field(), last.field());
+    assert_eq!(moves[16].field(), last.field());
+    assert_eq!(bytes[2].field(), moves[8].field());
+    assert_eq!(bytes[0].field(), BishopArt::new().snapshot().field());
+    assert_eq!(frames(BishopArt::new(), b"", Granularity::Byte).len(), 1);
+
+    // held back bytes of incomplete chunk are only walked by the last frame
+    let rev = BishopArt::builder(9, 5)
+      .decoding(Decoding {
+        reverse_chunk: 2,
+        ..Default::default()
+      })
+      .build()
+      .unwrap();
+    let chunked = frames(rev.clone(), b"abc", Granularity::Byte);
+    assert_eq!(chunked[1].field(), chunked[0].field());
+    assert_eq!(chunked[3].field(), rev.chain(b"abc").result().field());
+  }
+
+  #[test]
+  fn test_asciicast() {
+    let f = frames(BishopArt::new(), b"ab", Granularity::Byte);
+    let mut out = vec![];
+    asciicast(
+      &f,
+      &DrawingOptions::default(),
+      Duration::from_millis(250),
+      &mut out,
+    )
+    .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<_> = out.lines().collect();
+
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "{\"version\": 2, \"width\": 19, \"height\": 12}");
+    assert!(lines[1].starts_with("[0.000000, \"o\", \"+-----------------+\\r\\n|"));
+    assert!(lines[2].starts_with("[0.250000, \"o\", \"\\u001b[11A+---"));
+    assert!(lines[3].starts_with("[0.500000, \"o\", "));
+    assert!(lines[3].ends_with("+-----------------+\\r\\n\"]"));
+  }
+}
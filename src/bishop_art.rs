@@ -1,9 +1,28 @@
+use crate::color::{strip_escapes, ColorOptions, Painter, Part};
 use crate::errors::{Error, Result};
 use crate::vec2d::*;
 
+use std::collections::VecDeque;
+use std::fmt;
 use std::io::{self, Write};
+use std::slice;
+use std::sync::{Arc, Mutex};
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::*;
 
+mod builder;
+mod charset;
+mod decoding;
+mod frame;
+mod rules;
+
+pub use builder::BishopArtBuilder;
+pub use charset::Charset;
+pub use decoding::{BitOrder, Decoding, Descriptor};
+pub use frame::{Align, FrameChars, FrameStyle, Label, Overflow};
+pub use rules::{Diagonal, Hex, King, Knight, Layout, MoveRule, Nibble, Stairs};
+
+#[deprecated(note = "use `Charset`")]
 pub type CharList = Vec<char>;
 pub type FieldXY = Vec2D<isize>;
 pub type PosXY = (usize, usize);
@@ -17,25 +36,18 @@ pub const GEOMETRY_LIMITS_MIN: PosXY = (5, 5);
 /// Default field size (x, y): `(17, 9)`
 pub const DEFAULT_SIZE_WH: PosXY = (17, 9);
 
-/// Default char list (see [`DrawingOptions`])
+/// Default char list (see [`Charset`])
 ///
-/// [`DrawingOptions`]: ./struct.DrawingOptions.html
+/// [`Charset`]: ./struct.Charset.html
 pub const DEFAULT_CHARS: &str = " .o+=*BOX@%&#/^SE";
 
 /// Default text for frame borders
 pub const DEFAULT_TEXT: &str = "";
 
-const VALUE_MAX: isize = std::isize::MAX;
+const VALUE_MAX: isize = isize::MAX;
 const VALUE_S: isize = -1;
 const VALUE_E: isize = -2;
 
-#[inline]
-#[rustfmt::skip]
-fn u_add(a: usize, b: isize) -> usize {
-  let ub = b.abs() as usize;
-  if b < 0 { a - ub } else { a + ub }
-}
-
 #[inline]
 #[rustfmt::skip]
 fn bit_v(b: bool) -> isize {
@@ -49,62 +61,231 @@ fn bit_set_le(byte: u8, bit: u8) -> bool {
   ((byte >> (7 - bit)) & 1) == 1
 }
 
-fn bit_pairs(byte: u8) -> [(bool, bool); 4] {
-  let bs = |b| bit_set_le(byte, b);
-  let mut pairs = [(false, false); 4];
+/// Options for drawing methods
+#[derive(Debug, Clone)]
+pub struct DrawingOptions {
+  /// Chars used for fingerprint, see [`Charset`]
+  ///
+  /// [`Charset`]: ./struct.Charset.html
+  pub chars: Charset,
 
-  for c in 0..=3 {
-    let b = 7 - (c * 2) as u8;
-    pairs[c] = (bs(b - 1), bs(b));
-  }
+  /// Centered text for top frame border
+  pub top_text: String,
 
-  pairs
-}
+  /// Centered text for bottom frame border
+  pub bottom_text: String,
 
-/// Options for drawing methods
-pub struct DrawingOptions {
-  /// Vector of chars used for fingerprint
+  /// More labels for top frame border, placed after [`top_text`]
   ///
-  /// Each char is treated as:
+  /// Labels of each alignment keep their order:
   ///
-  /// Index  | Description             | Default          |
-  /// -------|-------------------------|------------------|
-  /// `0`    | Field background        | ` `              |
-  /// `1..n` | Chars used for drawing  | `.o+=*BOX@%&#/^` |
-  /// `n+1`  | Char for start position | `S`              |
-  /// `n+2`  | Char for last position  | `E`              |
+  /// - left ones are packed to the left corner, one fill char before each
+  /// - right ones are packed to the right corner, one fill char after each
+  /// - centered ones are centered on the border, moved aside
+  ///   to keep one fill char between them and the others
   ///
-  /// Each non-background char indicates how many
-  /// times bishop has been on this position.
+  /// Space goes to left labels first, then to right and centered ones.
+  /// Label that doesn't fit into the rest of the border is truncated,
+  /// or left out if not even its brackets and one char fit.
   ///
-  /// Start and end chars overwrites the real value.
+  /// [`top_text`]: ./struct.DrawingOptions.html#structfield.top_text
+  pub top_labels: Vec<Label>,
+
+  /// More labels for bottom frame border, placed after [`bottom_text`]
+  /// as [`top_labels`]
   ///
-  /// Char list must be at least 4 chars long,
-  /// but secure char list is at least 18 chars long
-  /// and only consists of clearly distinguishable symbols.
-  pub chars: CharList,
+  /// [`bottom_text`]: ./struct.DrawingOptions.html#structfield.bottom_text
+  /// [`top_labels`]: ./struct.DrawingOptions.html#structfield.top_labels
+  pub bottom_labels: Vec<Label>,
 
-  /// Text for top frame border
-  pub top_text: String,
+  /// Caption written down the left border, centered vertically
+  ///
+  /// Caption is cut to field height, chars that are not
+  /// one column wide are left out. Without frame it takes a column of its own.
+  pub left_caption: String,
 
-  /// Text for bottom frame border
-  pub bottom_text: String,
+  /// Caption written down the right border, as [`left_caption`]
+  ///
+  /// [`left_caption`]: ./struct.DrawingOptions.html#structfield.left_caption
+  pub right_caption: String,
+
+  /// Frame chars and label brackets
+  pub frame: FrameStyle,
+
+  /// Colors for drawing with ANSI escape sequences, plain text if `None`
+  pub color: Option<ColorOptions>,
 }
 
 impl DrawingOptions {
+  /// All labels of top or bottom border, text first
+  pub(crate) fn labels(&self, top: bool) -> Vec<Label> {
+    let (text, labels) = if top {
+      (&self.top_text, &self.top_labels)
+    } else {
+      (&self.bottom_text, &self.bottom_labels)
+    };
+    let mut all = Vec::with_capacity(labels.len() + 1);
+    all.push(Label::new(text.as_str(), Align::Center));
+    all.extend(labels.iter().cloned());
+    all
+  }
+}
+
+impl Default for DrawingOptions {
   /// Returns DrawingOptions with default parameters
-  pub fn default() -> DrawingOptions {
+  fn default() -> Self {
     DrawingOptions {
-      chars: DEFAULT_CHARS.chars().collect(),
+      chars: Charset::default(),
       top_text: DEFAULT_TEXT.into(),
       bottom_text: DEFAULT_TEXT.into(),
+      top_labels: Vec::new(),
+      bottom_labels: Vec::new(),
+      left_caption: String::new(),
+      right_caption: String::new(),
+      frame: FrameStyle::default(),
+      color: None,
     }
   }
 }
 
-impl Default for DrawingOptions {
-  fn default() -> Self {
-    DrawingOptions::default()
+/// Single move of the bishop, see [`BishopArt::steps()`] and [`Observer`]
+///
+/// [`BishopArt::steps()`]: ./struct.BishopArt.html#method.steps
+/// [`Observer`]: ./trait.Observer.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+  /// Index of input byte completing the move, counted from
+  /// the first byte pushed to `BishopArt` after chunk reversal
+  /// (see [`Decoding`])
+  ///
+  /// [`Decoding`]: ./struct.Decoding.html
+  pub byte: usize,
+
+  /// Index of the move among those completed by the byte,
+  /// for [`Diagonal`] moves it's the bit pair counted
+  /// in [`BitOrder`], from the least significant (`0`)
+  /// to the most significant (`3`) by default
+  ///
+  /// [`Diagonal`]: ./struct.Diagonal.html
+  /// [`BitOrder`]: ./enum.BitOrder.html
+  pub pair: usize,
+
+  /// Input bits of the move, see [`MoveRule`]
+  ///
+  /// [`MoveRule`]: ./trait.MoveRule.html
+  pub symbol: u8,
+
+  /// Two lowest bits of the symbol as `(a, b)`, for [`Diagonal`] moves
+  /// set `a` moves down, set `b` moves right, unset bits move up and left
+  ///
+  /// [`Diagonal`]: ./struct.Diagonal.html
+  pub bits: (bool, bool),
+
+  /// Position before the move
+  pub from: PosXY,
+
+  /// Position after the move, with [`Boundary`] applied
+  ///
+  /// [`Boundary`]: ./enum.Boundary.html
+  pub to: PosXY,
+
+  /// Move hit the wall and was changed according to [`Boundary`]
+  ///
+  /// [`Boundary`]: ./enum.Boundary.html
+  pub clamped: bool,
+}
+
+/// What happens when the bishop walks into the wall
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+  /// Blocked part of the move is dropped, as in OpenSSH
+  #[default]
+  Clamp,
+  /// Bishop comes out of the opposite wall (torus)
+  Wrap,
+  /// Blocked part of the move is reversed (bounce)
+  Reflect,
+}
+
+impl Boundary {
+  /// Move coordinate `p` by `v` within `0..=last`,
+  /// returns new coordinate and whether the wall was hit
+  fn apply(self, p: usize, v: isize, last: usize) -> (usize, bool) {
+    let (t, last) = (p as isize + v, last as isize);
+    if (0..=last).contains(&t) {
+      return (t as usize, false);
+    }
+    let p = match self {
+      Boundary::Clamp => t.clamp(0, last),
+      Boundary::Wrap => t.rem_euclid(last + 1),
+      Boundary::Reflect if last > 0 => {
+        let t = t.rem_euclid(last * 2);
+        t.min(last * 2 - t)
+      }
+      Boundary::Reflect => 0,
+    };
+    (p as usize, true)
+  }
+}
+
+/// Receiver of bishop's moves, see [`BishopArt::set_observer()`]
+///
+/// Implemented for closures taking `&Step`
+///
+/// [`BishopArt::set_observer()`]: ./struct.BishopArt.html#method.set_observer
+pub trait Observer {
+  /// Called after each move
+  fn step(&mut self, s: &Step);
+}
+
+impl<F: FnMut(&Step)> Observer for F {
+  fn step(&mut self, s: &Step) {
+    self(s)
+  }
+}
+
+/// Observer shared between `BishopArt` (and its clones) and the caller
+pub type SharedObserver = Arc<Mutex<dyn Observer + Send>>;
+
+#[derive(Clone, Default)]
+struct ObserverSlot(Option<SharedObserver>);
+
+impl fmt::Debug for ObserverSlot {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let s = if self.0.is_some() { "Some(..)" } else { "None" };
+    f.write_str(s)
+  }
+}
+
+/// How many times bishop has been on a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visits {
+  Empty,
+  Visited(usize),
+}
+
+/// Typed view of a field cell, see [`BishopResult::cell()`]
+///
+/// [`BishopResult::cell()`]: ./struct.BishopResult.html#method.cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+  /// Real visit count, not hidden by start and end marks
+  pub visits: Visits,
+
+  /// Bishop has started on this cell
+  pub start: bool,
+
+  /// Bishop has stopped on this cell
+  pub end: bool,
+}
+
+impl Cell {
+  /// Visit count as number
+  pub fn count(&self) -> usize {
+    match self.visits {
+      Visits::Empty => 0,
+      Visits::Visited(n) => n,
+    }
   }
 }
 
@@ -112,17 +293,40 @@ impl Default for DrawingOptions {
 ///
 /// [`BishopArt`]: ./struct.BishopArt.html
 pub struct BishopResult {
+  /// Counts with start and end marks
   field: FieldXY,
+  counts: Vec2D<usize>,
+  start: PosXY,
+  end: Option<PosXY>,
   size: PosXY,
+  layout: Layout,
 }
 
 /// Visualizer
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BishopArt {
   field_w: usize,
   field_h: usize,
+  /// Visit counts, start position is not marked
   map: FieldXY,
+  /// Counts don't grow above this value
+  max_count: isize,
+  boundary: Boundary,
+  rule: Arc<dyn MoveRule>,
+  decoding: Decoding,
+  start: PosXY,
   pos: PosXY,
+  bytes: usize,
+  /// Bytes of incomplete reversal chunk
+  chunk: Vec<u8>,
+  /// Bytes ready to be split into moves
+  queue: VecDeque<u8>,
+  /// Input bits not consumed by moves yet, in `decoding.order`
+  bit_buf: u32,
+  bit_len: u8,
+  /// Moves made since the last pushed byte
+  byte_moves: usize,
+  observer: ObserverSlot,
 }
 
 impl BishopArt {
@@ -137,9 +341,12 @@ impl BishopArt {
   ///
   /// # Errors
   ///
-  /// Returns [`BishopError::Err`] on limits violation
+  /// Returns [`BadGeometry`] on limits violation,
+  /// use [`builder()`] to get [`BadSize`] with the requested size
   ///
-  /// [`BishopError::Err`]: ../enum.BishopError.html
+  /// [`BadGeometry`]: ../errors/enum.Error.html#variant.BadGeometry
+  /// [`BadSize`]: ../errors/enum.Error.html#variant.BadSize
+  /// [`builder()`]: ./struct.BishopArt.html#method.builder
   /// [`GEOMETRY_LIMITS_MIN`]: ./constant.GEOMETRY_LIMITS_MIN.html
   /// [`GEOMETRY_LIMITS_MAX`]: ./constant.GEOMETRY_LIMITS_MAX.html
   pub fn with_size(w: usize, h: usize) -> Result<BishopArt> {
@@ -152,17 +359,15 @@ impl BishopArt {
       });
     }
 
-    let pos = ((w - 1) / 2, (h - 1) / 2);
-    let mut map = Vec2D::new(w, h, 0isize);
-
-    map[pos] = VALUE_S;
+    BishopArtBuilder::new(w, h).build()
+  }
 
-    Ok(BishopArt {
-      field_w: w,
-      field_h: h,
-      map,
-      pos,
-    })
+  /// Creates builder for BishopArt with custom start position,
+  /// geometry limits and walk parameters (see [`BishopArtBuilder`])
+  ///
+  /// [`BishopArtBuilder`]: ./struct.BishopArtBuilder.html
+  pub fn builder(w: usize, h: usize) -> BishopArtBuilder {
+    BishopArtBuilder::new(w, h)
   }
 
   /// Creates new BishopArt instance with default field size
@@ -174,16 +379,142 @@ impl BishopArt {
     }
   }
 
-  #[rustfmt::skip]
-  fn mov(&self, (x, y): PosXY, a: bool, b: bool) -> PosXY {
-    let (lx, ly) = (self.field_w - 1, self.field_h - 1);
-    let (xs, ys, xe, ye) = (x == 0, y == 0, x == lx, y == ly);
-    let (mut xv, mut yv) = (bit_v(b), bit_v(a));
-    if ys && yv < 0 { yv = 0 };
-    if xs && xv < 0 { xv = 0 };
-    if ye && yv > 0 { yv = 0 };
-    if xe && xv > 0 { xv = 0 };
-    (u_add(x, xv), u_add(y, yv))
+  /// New position and whether the move hit the wall
+  fn mov(&self, pos: PosXY, symbol: u8) -> (PosXY, bool) {
+    let (dx, dy) = self.rule.delta(symbol, pos);
+    let (x, xw) = self.boundary.apply(pos.0, dx, self.field_w - 1);
+    let (y, yw) = self.boundary.apply(pos.1, dy, self.field_h - 1);
+    ((x, y), xw || yw)
+  }
+
+  /// Queue the next input byte, it's held back until its chunk is complete
+  fn push_byte(&mut self, byte: u8) {
+    self.chunk.push(byte);
+    if self.chunk.len() >= self.decoding.reverse_chunk {
+      self.queue.extend(self.chunk.drain(..).rev());
+    }
+  }
+
+  /// Take the next move symbol, loading queued bytes as needed
+  fn next_symbol(&mut self) -> Option<u8> {
+    let bits = self.rule.bits();
+    while self.bit_len < bits {
+      let byte = u32::from(self.queue.pop_front()?);
+      match self.decoding.order {
+        BitOrder::Lsb => self.bit_buf |= byte << self.bit_len,
+        BitOrder::Msb => self.bit_buf = (self.bit_buf << 8) | byte,
+      }
+      self.bit_len += 8;
+      self.bytes += 1;
+      self.byte_moves = 0;
+    }
+
+    let mask = (1 << bits) - 1;
+    self.bit_len -= bits;
+    let symbol = match self.decoding.order {
+      BitOrder::Lsb => {
+        let s = self.bit_buf & mask;
+        self.bit_buf >>= bits;
+        s
+      }
+      BitOrder::Msb => {
+        let s = (self.bit_buf >> self.bit_len) & mask;
+        self.bit_buf &= (1 << self.bit_len) - 1;
+        s
+      }
+    };
+    Some(symbol as u8)
+  }
+
+  /// Make move if enough input is queued
+  fn step(&mut self) -> Option<Step> {
+    let symbol = self.next_symbol()?;
+
+    let from = self.pos;
+    let (pos, clamped) = self.mov(from, symbol);
+    let p = &mut self.map[pos];
+    let v = *p;
+
+    self.pos = pos;
+
+    if (0..self.max_count).contains(&v) {
+      *p = v + 1;
+    }
+
+    let s = Step {
+      byte: self.bytes - 1,
+      pair: self.byte_moves,
+      symbol,
+      bits: (symbol & 2 != 0, symbol & 1 != 0),
+      from,
+      to: pos,
+      clamped,
+    };
+    self.byte_moves += 1;
+    if let Some(o) = &self.observer.0 {
+      // poisoned observer has panicked before, it gets no more moves
+      if let Ok(mut o) = o.lock() {
+        o.step(&s);
+      }
+    }
+    Some(s)
+  }
+
+  /// Attach observer receiving every move made by [`input()`],
+  /// [`steps()`] and `io::Write` implementation
+  ///
+  /// Clones of this `BishopArt` share the observer. If the observer
+  /// panics, its mutex is poisoned and it's silently skipped
+  /// for the rest of the walk, the walk itself goes on
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use bishop::*;
+  /// # use bishop::bishop_art::Step;
+  /// use std::sync::{Arc, Mutex};
+  ///
+  /// let path = Arc::new(Mutex::new(vec![]));
+  /// let p = path.clone();
+  ///
+  /// let mut art = BishopArt::new();
+  /// art.set_observer(Arc::new(Mutex::new(move |s: &Step| p.lock().unwrap().push(s.to))));
+  /// art.input(b"foo");
+  /// assert_eq!(path.lock().unwrap().len(), 12);
+  /// ```
+  ///
+  /// [`input()`]: ./struct.BishopArt.html#method.input
+  /// [`steps()`]: ./struct.BishopArt.html#method.steps
+  pub fn set_observer(&mut self, o: SharedObserver) {
+    self.observer.0 = Some(o);
+  }
+
+  /// Detach observer, returning it
+  pub fn take_observer(&mut self) -> Option<SharedObserver> {
+    self.observer.0.take()
+  }
+
+  /// Push bytes like [`input()`] one move at a time,
+  /// returning iterator of made moves
+  ///
+  /// Moves are made lazily, so remaining data is
+  /// not pushed if the iterator is dropped
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use bishop::*;
+  /// let mut art = BishopArt::new();
+  /// let walls = art.steps(b"\x00\x00").filter(|s| s.clamped).count();
+  /// assert_eq!(walls, 4);
+  /// ```
+  ///
+  /// [`input()`]: ./struct.BishopArt.html#method.input
+  pub fn steps<'a>(&'a mut self, data: &'a [u8]) -> Steps<'a> {
+    Steps {
+      art: self,
+      data: data.iter(),
+    }
   }
 
   /// Push bytes for visualising.
@@ -198,21 +529,47 @@ impl BishopArt {
   ///
   /// [`result()`]: ./struct.BishopArt.html#method.result
   pub fn input<T: AsRef<[u8]>>(&mut self, i: T) {
-    for &byte in i.as_ref() {
-      for &(a, b) in &bit_pairs(byte) {
-        let pos = self.mov(self.pos, a, b);
-        let p = &mut self.map[pos];
-        let v = *p;
-
-        self.pos = pos;
+    self.input_with(i, |_| ());
+  }
 
-        if v >= 0 && v < VALUE_MAX {
-          *p = v + 1;
-        }
+  /// Same as [`input()`] but calls `f` after each move
+  /// of the bishop (four moves per byte with default [`MoveRule`])
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use bishop::*;
+  /// let mut art = BishopArt::new();
+  /// let mut moves = 0;
+  /// art.input_with(b"foo", |_| moves += 1);
+  /// assert_eq!(moves, 12);
+  /// ```
+  ///
+  /// [`input()`]: ./struct.BishopArt.html#method.input
+  /// [`MoveRule`]: ./trait.MoveRule.html
+  pub fn input_with<T: AsRef<[u8]>, F: FnMut(&BishopArt)>(&mut self, i: T, mut f: F) {
+    for &byte in i.as_ref() {
+      self.push_byte(byte);
+      while self.step().is_some() {
+        f(self);
       }
     }
   }
 
+  /// Walk parameters needed to reproduce arts of this `BishopArt`
+  pub fn descriptor(&self) -> Descriptor {
+    Descriptor {
+      version: Descriptor::VERSION,
+      size: (self.field_w, self.field_h),
+      start: self.start,
+      moves: self.rule.name().to_string(),
+      bits: self.rule.bits(),
+      decoding: self.decoding,
+      boundary: self.boundary,
+      saturation: self.max_count as usize,
+    }
+  }
+
   /// Same as [`input()`] but suitable for chaining
   ///
   /// # Example
@@ -233,17 +590,21 @@ impl BishopArt {
 
   /// Finalize the field and return [`BishopResult`]
   ///
-  /// Note that this method consumes `BishopArt`
+  /// Note that this method consumes `BishopArt`.
+  /// Incomplete reversal chunk is walked as is,
+  /// input bits not making a whole move are dropped
   ///
   /// [`BishopResult`]: ./struct.BishopResult.html
-  pub fn result(self) -> BishopResult {
-    let mut f = self.map;
-    f[self.pos] = VALUE_E;
+  pub fn result(mut self) -> BishopResult {
+    let chunk: Vec<_> = self.chunk.drain(..).rev().collect();
+    self.queue.extend(chunk);
+    while self.step().is_some() {}
+    BishopResult::new(self.map, self.start, Some(self.pos), self.rule.layout())
+  }
 
-    BishopResult {
-      field: f,
-      size: (self.field_w, self.field_h),
-    }
+  /// Current field without last position mark, doesn't consume `BishopArt`
+  pub(crate) fn snapshot(&self) -> BishopResult {
+    BishopResult::new(self.map.clone(), self.start, None, self.rule.layout())
   }
 
   /// Draw resulting field to String using
@@ -275,6 +636,12 @@ impl BishopArt {
   }
 }
 
+impl Default for BishopArt {
+  fn default() -> Self {
+    BishopArt::new()
+  }
+}
+
 impl Write for BishopArt {
   fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
     let ln = buf.len();
@@ -290,7 +657,57 @@ impl Write for BishopArt {
   }
 }
 
+/// Iterator of moves returned by [`BishopArt::steps()`]
+///
+/// [`BishopArt::steps()`]: ./struct.BishopArt.html#method.steps
+pub struct Steps<'a> {
+  art: &'a mut BishopArt,
+  data: slice::Iter<'a, u8>,
+}
+
+impl Iterator for Steps<'_> {
+  type Item = Step;
+
+  fn next(&mut self) -> Option<Step> {
+    loop {
+      if let Some(s) = self.art.step() {
+        return Some(s);
+      }
+      self.art.push_byte(*self.data.next()?);
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let art = &self.art;
+    let moves = |bytes: usize| (bytes * 8 + art.bit_len as usize) / art.rule.bits() as usize;
+    let all = moves(self.data.len() + art.chunk.len() + art.queue.len());
+    // bytes of incomplete chunk are held back
+    if art.decoding.reverse_chunk > 1 {
+      (moves(art.queue.len()), Some(all))
+    } else {
+      (all, Some(all))
+    }
+  }
+}
+
 impl BishopResult {
+  fn new(counts: FieldXY, start: PosXY, end: Option<PosXY>, layout: Layout) -> Self {
+    let mut field = counts.clone();
+    field[start] = VALUE_S;
+    if let Some(end) = end {
+      field[end] = VALUE_E;
+    }
+
+    BishopResult {
+      size: (field.w, field.h),
+      field,
+      counts: counts.map(|&v| v as usize),
+      start,
+      end,
+      layout,
+    }
+  }
+
   /// Get width of resulting field
   pub fn width(&self) -> usize {
     self.size.0
@@ -302,103 +719,258 @@ impl BishopResult {
   }
 
   /// Get resulting field
+  ///
+  /// Start and end positions are marked with negative values
+  /// hiding their visit counts, see [`cell()`] for the real ones
+  ///
+  /// [`cell()`]: ./struct.BishopResult.html#method.cell
   pub fn field(&self) -> &FieldXY {
     &self.field
   }
 
-  fn fill_dash(s: &mut String, c: usize) {
-    for _ in 0..c {
-      s.push('-')
+  /// Get typed view of cell at `(x, y)`
+  ///
+  /// # Panics
+  ///
+  /// Panics if position is outside of the field
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use bishop::*;
+  /// use bishop::bishop_art::Visits;
+  ///
+  /// // up-left, down-right, up-left, down-right
+  /// let res = BishopArt::new().chain([0xcc]).result();
+  /// let c = res.cell(8, 4);
+  /// assert!(c.start && c.end);
+  /// assert_eq!(c.visits, Visits::Visited(2));
+  /// ```
+  pub fn cell(&self, x: usize, y: usize) -> Cell {
+    let visits = match *self.counts.get(x, y) {
+      0 => Visits::Empty,
+      n => Visits::Visited(n),
+    };
+
+    Cell {
+      visits,
+      start: (x, y) == self.start,
+      end: Some((x, y)) == self.end,
     }
   }
 
-  /// Draw resulting field to String using
-  /// parameters from [`DrawingOptions`]
+  /// Get typed view of the whole field
+  pub fn cells(&self) -> Vec2D<Cell> {
+    let mut cells = Vec2D::new(self.size.0, self.size.1, self.cell(0, 0));
+    for y in 0..self.size.1 {
+      for x in 0..self.size.0 {
+        cells[(x, y)] = self.cell(x, y);
+      }
+    }
+    cells
+  }
+
+  /// Get start position
+  pub fn start(&self) -> PosXY {
+    self.start
+  }
+
+  /// Get last position, `None` for unfinished fields from [`gif::walk()`]
   ///
-  /// # Panics
+  /// [`gif::walk()`]: ../gif/fn.walk.html
+  pub fn end(&self) -> Option<PosXY> {
+    self.end
+  }
+
+  /// Get cell layout of the [`MoveRule`] used for the walk
   ///
-  /// This function panics if char list length
-  /// is less than 4 or more than isize::MAX
+  /// [`MoveRule`]: ./trait.MoveRule.html
+  pub fn layout(&self) -> Layout {
+    self.layout
+  }
+
+  /// Width of the field drawn as text, without side borders
   ///
-  /// [`DrawingOptions`]: ./struct.DrawingOptions.html
-  pub fn draw_with_opts(&self, o: &DrawingOptions) -> String {
-    let (w, h) = self.size;
-    let chr: &Vec<char> = &o.chars;
-    let chr_ln = chr.len();
+  /// Same as [`width()`] for square layout, hex layout
+  /// takes two columns per cell. Counted in chars, callers
+  /// multiply it by [`Charset::width()`] to get terminal columns
+  ///
+  /// [`Charset::width()`]: ./struct.Charset.html#method.width
+  /// [`width()`]: ./struct.BishopResult.html#method.width
+  pub fn text_width(&self) -> usize {
+    match self.layout {
+      Layout::Square => self.size.0,
+      Layout::Hex => self.size.0 * 2,
+    }
+  }
 
-    if chr_ln < 4 || chr_ln > (std::isize::MAX as usize) {
-      panic!("Char list length must be 4 <= n <= isize::MAX");
+  pub(crate) fn fill_dash(s: &mut String, c: usize) {
+    for _ in 0..c {
+      s.push('-')
     }
+  }
 
-    let chr_sub_ln = (chr_ln - 2) as isize; // length w/o SE chars
-    let (chr_last, chr_s, chr_e) = match chr[chr_ln - 3..] {
-      [l, s, e] => (l, s, e),
-      _ => unreachable!(),
-    };
+  /// Map field value to drawn part
+  pub(crate) fn part(v: isize) -> Part {
+    match v {
+      VALUE_E => Part::End,
+      VALUE_S => Part::Start,
+      v if v < 0 => unreachable!(),
+      0 => Part::Background,
+      v => Part::Visits(v as usize),
+    }
+  }
 
-    let v_frame = |s: &mut String, text: &str| {
-      s.push('+');
-      if text.is_empty() {
-        Self::fill_dash(s, w)
-      } else {
-        let (text_idx, text_ln) = {
-          let real_w = w - 2;
-          let mut size = 0usize;
-          let mut last = 0usize;
-
-          for (i, c) in text.char_indices() {
-            let sz = size + c.width().unwrap_or(0);
-            if sz <= real_w {
-              last = i + c.len_utf8();
-              size = sz;
-            } else {
-              break;
-            }
-          }
+  /// Map field value to drawn part and char
+  pub(crate) fn cell_char(v: isize, chr: &Charset) -> (Part, char) {
+    (Self::part(v), chr.cell(v))
+  }
 
-          (last, size)
-        };
+  /// Field lines with side border chars of each row as drawn parts and chars
+  ///
+  /// Hex layout separates cells with spaces
+  /// and shifts odd rows right by one char
+  fn field_lines<'a, L, R>(&self, chr: &Charset, left: L, right: R) -> Vec<Vec<(Part, char)>>
+  where
+    L: Fn(usize) -> Option<&'a str>,
+    R: Fn(usize) -> Option<&'a str>,
+  {
+    let gap = vec![(Part::Background, ' '); chr.width()];
 
-        let fill_w = w - (text_ln + 2);
-        let (dash, pad) = (fill_w / 2, fill_w % 2);
-        Self::fill_dash(s, dash);
-        s.push('[');
-        s.push_str(&text[..text_idx]);
-        s.push(']');
-        Self::fill_dash(s, dash + pad);
-      }
-      s.push_str("+\n");
-    };
+    self
+      .field
+      .rows()
+      .enumerate()
+      .map(|(y, row)| {
+        let mut l = Vec::with_capacity(self.text_width() * chr.width() + 2);
+        l.extend(
+          left(y)
+            .into_iter()
+            .flat_map(str::chars)
+            .map(|c| (Part::Frame, c)),
+        );
+        if self.layout == Layout::Hex && y % 2 == 1 {
+          l.extend_from_slice(&gap);
+        }
+        for (x, &v) in row.iter().enumerate() {
+          if self.layout == Layout::Hex && x > 0 {
+            l.extend_from_slice(&gap);
+          }
+          l.push(Self::cell_char(v, chr));
+        }
+        if self.layout == Layout::Hex && y % 2 == 0 {
+          l.extend_from_slice(&gap);
+        }
+        l.extend(
+          right(y)
+            .into_iter()
+            .flat_map(str::chars)
+            .map(|c| (Part::Frame, c)),
+        );
+        l
+      })
+      .collect()
+  }
 
-    let line = |s: &mut String, y: usize| {
-      s.push('|');
-      for x in 0..w {
-        let c = match *self.field.get(x, y) {
-          VALUE_E => chr_e,
-          VALUE_S => chr_s,
-          v if v < 0 => unreachable!(),
-          v if v < chr_sub_ln => chr[v as usize],
-          _ => chr_last,
-        };
-        s.push(c);
+  /// Push lines to `p`
+  fn paint_lines(p: &mut Painter, lines: Vec<Vec<(Part, char)>>) {
+    for l in lines {
+      for (part, c) in l {
+        p.push(part, c);
       }
-      s.push_str("|\n")
+      p.newline();
+    }
+  }
+
+  /// Push field lines with ASCII side borders to `s`
+  ///
+  /// Char list is expected to be already checked
+  /// by the caller (see [`draw_with_opts()`])
+  ///
+  /// [`draw_with_opts()`]: ./struct.BishopResult.html#method.draw_with_opts
+  pub(crate) fn draw_lines(&self, s: &mut String, chr: &Charset) {
+    let mut p = Painter::new(0, None);
+    Self::paint_lines(&mut p, self.field_lines(chr, |_| Some("|"), |_| Some("|")));
+    s.push_str(&p.finish());
+  }
+
+  /// All drawn lines with frame as parts and chars
+  pub(crate) fn lines(&self, o: &DrawingOptions) -> Vec<Vec<(Part, char)>> {
+    let (w, h) = (self.text_width() * o.chars.width(), self.size.1);
+    let side = o.frame.side().map(String::from);
+    let side = side.as_deref();
+    let left = o.frame.caption(&o.left_caption, h);
+    let right = o.frame.caption(&o.right_caption, h);
+    // without frame captions get their own columns, blank in borders
+    let pad = |cap: &Option<_>| side.or_else(|| cap.as_ref().map(|_| " "));
+    fn column<'a>(
+      cap: &Option<(usize, Vec<&'a str>)>,
+      y: usize,
+      pad: Option<&'a str>,
+    ) -> Option<&'a str> {
+      let c = cap
+        .as_ref()
+        .and_then(|(start, c)| c.get(y.checked_sub(*start)?).copied());
+      c.or(pad)
+    }
+    let line_w = w + usize::from(pad(&left).is_some()) + usize::from(pad(&right).is_some());
+    let frame = |chars: &mut dyn Iterator<Item = char>| chars.map(|c| (Part::Frame, c)).collect();
+
+    let border = |top, labels: &[Label]| {
+      let l = o.frame.border(top, w, labels)?;
+      let blank = |cap| if side.is_none() { pad(cap) } else { None };
+      let mut chars = blank(&left)
+        .into_iter()
+        .chain(Some(l.as_str()))
+        .chain(blank(&right))
+        .flat_map(str::chars);
+      Some(frame(&mut chars))
+    };
+    let wrapped = |labels: &[Label]| -> Vec<Vec<(Part, char)>> {
+      let lines = o.frame.wrapped(w, labels, line_w).into_iter();
+      lines
+        .map(|(col, text, tw)| {
+          let after = line_w.saturating_sub(col + tw);
+          let mut chars = std::iter::repeat_n(' ', col)
+            .chain(text.chars())
+            .chain(std::iter::repeat_n(' ', after));
+          frame(&mut chars)
+        })
+        .collect()
     };
 
-    // (width + 2x pipe + \n) * (height + top + bottom)
-    let cap = (w + 3) * (h + 2);
-    let mut out = String::with_capacity(cap);
+    let (top, bottom) = (o.labels(true), o.labels(false));
+    let mut lines = wrapped(&top);
+    lines.extend(border(true, &top));
+    lines.extend(self.field_lines(
+      &o.chars,
+      |y| column(&left, y, pad(&left)),
+      |y| column(&right, y, pad(&right)),
+    ));
+    lines.extend(border(false, &bottom));
+    lines.extend(wrapped(&bottom));
+    lines
+  }
 
-    v_frame(&mut out, &o.top_text);
+  /// Draw resulting field to String using
+  /// parameters from [`DrawingOptions`]
+  ///
+  /// If [`color`] is set, parts of the art are wrapped
+  /// in ANSI escape sequences
+  ///
+  /// [`DrawingOptions`]: ./struct.DrawingOptions.html
+  /// [`color`]: ./struct.DrawingOptions.html#structfield.color
+  pub fn draw_with_opts(&self, o: &DrawingOptions) -> String {
+    let (w, h) = (self.text_width(), self.size.1);
 
-    for y in 0..h {
-      line(&mut out, y);
-    }
+    // (width + 2x pipe + \n) * (height + top + bottom)
+    let cap = (w + 3) * (h + 2);
+    let mut out = Painter::new(cap, o.color.as_ref());
 
-    v_frame(&mut out, &o.bottom_text);
+    Self::paint_lines(&mut out, self.lines(o));
 
     //eprintln!("draw cap: {}, real cap: {}", cap, out.capacity());
-    out
+    out.finish()
   }
 
   /// Draw resulting field to String using default parameters
@@ -407,6 +979,66 @@ impl BishopResult {
   }
 }
 
+/// Longest prefix of `text` that fits into `max` columns, and its width
+///
+/// Text is cut between grapheme clusters, so combining marks
+/// and emoji sequences are never split
+pub(crate) fn fit_width(text: &str, max: usize) -> (&str, usize) {
+  let mut size = 0usize;
+  let mut last = 0usize;
+
+  for (i, g) in text.grapheme_indices(true) {
+    let sz = size + g.width();
+    if sz <= max {
+      last = i + g.len();
+      size = sz;
+    } else {
+      break;
+    }
+  }
+
+  (&text[..last], size)
+}
+
+/// Display width of line, ignoring ANSI escape sequences
+pub(crate) fn line_width(l: &str) -> usize {
+  if l.contains('\x1b') {
+    strip_escapes(l).width()
+  } else {
+    l.width()
+  }
+}
+
+/// Join drawn arts horizontally, separated by `gap` spaces
+///
+/// Arts of different height are aligned to the top,
+/// colored arts are supported
+pub fn side_by_side<T: AsRef<str>>(arts: &[T], gap: usize) -> String {
+  let arts: Vec<Vec<&str>> = arts.iter().map(|a| a.as_ref().lines().collect()).collect();
+  let widths: Vec<usize> = arts
+    .iter()
+    .map(|a| a.iter().map(|l| line_width(l)).max().unwrap_or(0))
+    .collect();
+  let height = arts.iter().map(|a| a.len()).max().unwrap_or(0);
+
+  let mut out = String::new();
+  for y in 0..height {
+    // last art that still has a line here, no padding after it
+    let last = arts.iter().rposition(|a| y < a.len()).unwrap_or(0);
+    for (i, a) in arts[..=last].iter().enumerate() {
+      let l = a.get(y).copied().unwrap_or("");
+      out.push_str(l);
+      if i < last {
+        let pad = widths[i] - line_width(l) + gap;
+        out.extend(std::iter::repeat_n(' ', pad));
+      }
+    }
+    out.push('\n');
+  }
+
+  out
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -426,6 +1058,159 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_cells() {
+    // up-left and back twice, to the bottom-right corner and to the top-left one
+    let res = BishopArt::with_size(5, 5)
+      .unwrap()
+      .chain([0xcc, 0xff, 0x00])
+      .result();
+
+    assert_eq!(res.start(), (2, 2));
+    assert_eq!(res.end(), Some((0, 0)));
+    assert_eq!(*res.field().get(2, 2), VALUE_S);
+    assert_eq!(*res.field().get(0, 0), VALUE_E);
+    assert_eq!(
+      res.cell(2, 2),
+      Cell {
+        visits: Visits::Visited(3),
+        start: true,
+        end: false,
+      }
+    );
+    assert_eq!(res.cell(1, 1).visits, Visits::Visited(3));
+    assert_eq!(res.cell(4, 4).count(), 3);
+    assert!(res.cell(0, 0).end);
+    assert_eq!(res.cell(0, 0).count(), 1);
+    assert_eq!(res.cell(0, 4).visits, Visits::Empty);
+
+    let cells = res.cells();
+    let total: usize = cells.iter_xy().map(|(_, c)| c.count()).sum();
+    assert_eq!(total, 12);
+    assert_eq!(cells.rows().nth(3).unwrap()[3].count(), 2);
+
+    let snap = BishopArt::new().chain([0xcc]).snapshot();
+    assert_eq!(snap.end(), None);
+    assert_eq!(*snap.field().get(8, 4), VALUE_S);
+  }
+
+  #[test]
+  fn test_boundary() {
+    let draw = |b| {
+      BishopArt::builder(5, 5)
+        .boundary(b)
+        .build()
+        .unwrap()
+        .chain([0x00, 0x00])
+        .draw()
+    };
+
+    assert_eq!(
+      draw(Boundary::Clamp),
+      "+-----+\n|E    |\n| .   |\n|  S  |\n|     |\n|     |\n+-----+\n"
+    );
+    assert_eq!(
+      draw(Boundary::Wrap),
+      "+-----+\n|o    |\n| o   |\n|  S  |\n|   . |\n|    E|\n+-----+\n"
+    );
+    assert_eq!(
+      draw(Boundary::Reflect),
+      "+-----+\n|E    |\n| =   |\n|  S  |\n|     |\n|     |\n+-----+\n"
+    );
+
+    // up-right along the top wall
+    let path = |b| {
+      let mut art = BishopArt::builder(5, 5)
+        .start(2, 0)
+        .boundary(b)
+        .build()
+        .unwrap();
+      let p: Vec<_> = art.steps(&[0x55]).map(|s| (s.to, s.clamped)).collect();
+      p
+    };
+    assert_eq!(
+      path(Boundary::Clamp),
+      [
+        ((3, 0), true),
+        ((4, 0), true),
+        ((4, 0), true),
+        ((4, 0), true)
+      ]
+    );
+    assert_eq!(
+      path(Boundary::Wrap),
+      [
+        ((3, 4), true),
+        ((4, 3), false),
+        ((0, 2), true),
+        ((1, 1), false)
+      ]
+    );
+    assert_eq!(
+      path(Boundary::Reflect),
+      [
+        ((3, 1), true),
+        ((4, 0), false),
+        ((3, 1), true),
+        ((4, 0), false)
+      ]
+    );
+
+    // two-cell jumps are reflected twice on narrow field
+    assert_eq!(Boundary::Reflect.apply(0, -2, 1), (0, true));
+    assert_eq!(Boundary::Reflect.apply(1, 2, 1), (1, true));
+    assert_eq!(Boundary::Wrap.apply(3, 2, 4), (0, true));
+    assert_eq!(Boundary::Clamp.apply(1, -2, 4), (0, true));
+  }
+
+  #[test]
+  fn test_steps() {
+    let data = [0x00, 0x00, 0xf4, 0x1b];
+    let path = Arc::new(Mutex::new(vec![]));
+    let p = path.clone();
+
+    let mut art = BishopArt::new();
+    art.set_observer(Arc::new(Mutex::new(move |s: &Step| {
+      p.lock().unwrap().push(*s)
+    })));
+    let steps: Vec<_> = art.steps(&data[..1]).collect();
+    art.write_all(&data[1..]).unwrap();
+
+    assert_eq!(steps.len(), 4);
+    assert_eq!(
+      steps[0],
+      Step {
+        byte: 0,
+        pair: 0,
+        symbol: 0,
+        bits: (false, false),
+        from: (8, 4),
+        to: (7, 3),
+        clamped: false,
+      }
+    );
+    assert_eq!(steps[3].to, (4, 0));
+
+    let path = path.lock().unwrap();
+    assert_eq!(path.len(), 16);
+    assert_eq!(&path[..4], &steps[..]);
+    assert_eq!(
+      (path[4].byte, path[4].to, path[4].clamped),
+      (1, (3, 0), true)
+    );
+    assert_eq!(path[15].byte, 3);
+    assert!(path.windows(2).all(|w| w[0].to == w[1].from));
+
+    let pairs: Vec<_> = path[8..12].iter().map(|s| (s.pair, s.symbol)).collect();
+    assert_eq!(pairs, [(0, 0), (1, 1), (2, 3), (3, 3)]);
+
+    art.take_observer();
+    assert_eq!(
+      art.result().field(),
+      BishopArt::new().chain(data).result().field()
+    );
+  }
+
   #[test]
   fn test_bit_pairs() {
     let n = 0xf4_u8;
@@ -435,7 +1220,33 @@ mod tests {
       .map(|(a, b)| (*a == 1, *b == 1))
       .collect();
 
-    assert_eq!(bit_pairs(n)[..], nb[..]);
+    let pairs: Vec<_> = BishopArt::new().steps(&[n]).map(|s| s.bits).collect();
+    assert_eq!(pairs[..], nb[..]);
+  }
+
+  #[test]
+  fn test_hex() {
+    // right, down-right twice, left and up-left
+    let res = BishopArt::builder(5, 5)
+      .move_rule(Hex)
+      .build()
+      .unwrap()
+      .chain([0xdd, 0x08])
+      .result();
+    assert_eq!(res.layout(), Layout::Hex);
+    assert_eq!(res.text_width(), 10);
+    assert_eq!(
+      res.draw(),
+      "\
++----------+
+|          |
+|          |
+|    S .   |
+|     E .  |
+|      . . |
++----------+
+"
+    );
   }
 
   // reference arts are taken from page 16 of specification
@@ -492,6 +1303,12 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_side_by_side() {
+    let out = side_by_side(&["ab\ncd\nef\n", "1\n2\n"], 2);
+    assert_eq!(out, "ab  1\ncd  2\nef\n");
+  }
+
   #[test]
   fn test_walker_ref() {
     let mut chars = HashMap::new();
@@ -511,8 +1328,7 @@ mod tests {
         .lines()
         .filter(|l| !l.starts_with('+'))
         .map(|l| l.trim_matches('|'))
-        .map(|l| l.chars().map(|c| chars[&c]).collect::<Vec<_>>())
-        .flatten()
+        .flat_map(|l| l.chars().map(|c| chars[&c]).collect::<Vec<_>>())
         .collect();
 
       let data = hex::decode(hash).unwrap();
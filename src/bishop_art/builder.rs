@@ -0,0 +1,245 @@
+use super::*;
+
+/// Builder for [`BishopArt`] with custom parameters
+///
+/// # Example
+///
+/// ```rust
+/// # use bishop::*;
+/// let art = BishopArt::builder(30, 15)
+///     .start(0, 0)
+///     .saturation(14)
+///     .build()
+///     .unwrap();
+/// println!("{}", art.chain(b"foo").draw());
+/// ```
+///
+/// [`BishopArt`]: ./struct.BishopArt.html
+#[derive(Debug, Clone)]
+pub struct BishopArtBuilder {
+  size: PosXY,
+  start: Option<PosXY>,
+  limits: (PosXY, PosXY),
+  saturation: usize,
+  boundary: Boundary,
+  rule: Arc<dyn MoveRule>,
+  decoding: Decoding,
+}
+
+impl BishopArtBuilder {
+  /// Minimum field size allowed by [`limits()`]: `(2, 1)`
+  ///
+  /// [`limits()`]: ./struct.BishopArtBuilder.html#method.limits
+  pub const LIMITS_MIN: PosXY = (2, 1);
+
+  /// Creates builder for `w`x`h` field with default parameters
+  pub fn new(w: usize, h: usize) -> Self {
+    BishopArtBuilder {
+      size: (w, h),
+      start: None,
+      limits: (GEOMETRY_LIMITS_MIN, GEOMETRY_LIMITS_MAX),
+      saturation: VALUE_MAX as usize,
+      boundary: Boundary::default(),
+      rule: Arc::new(Diagonal),
+      decoding: Decoding::default(),
+    }
+  }
+
+  /// Field size
+  pub fn size(mut self, w: usize, h: usize) -> Self {
+    self.size = (w, h);
+    self
+  }
+
+  /// Start position, center of the field by default
+  pub fn start(mut self, x: usize, y: usize) -> Self {
+    self.start = Some((x, y));
+    self
+  }
+
+  /// Field size limits instead of [`GEOMETRY_LIMITS_MIN`]
+  /// and [`GEOMETRY_LIMITS_MAX`]
+  ///
+  /// `min` can't be smaller than [`LIMITS_MIN`]
+  ///
+  /// [`GEOMETRY_LIMITS_MIN`]: ./constant.GEOMETRY_LIMITS_MIN.html
+  /// [`GEOMETRY_LIMITS_MAX`]: ./constant.GEOMETRY_LIMITS_MAX.html
+  /// [`LIMITS_MIN`]: ./struct.BishopArtBuilder.html#associatedconstant.LIMITS_MIN
+  pub fn limits(mut self, min: PosXY, max: PosXY) -> Self {
+    self.limits = (min, max);
+    self
+  }
+
+  /// Maximum visit count of a cell, further visits are not counted
+  ///
+  /// Unlimited (`isize::MAX`) by default
+  pub fn saturation(mut self, max: usize) -> Self {
+    self.saturation = max;
+    self
+  }
+
+  /// What happens when the bishop walks into the wall,
+  /// [`Boundary::Clamp`] by default
+  ///
+  /// [`Boundary::Clamp`]: ./enum.Boundary.html#variant.Clamp
+  pub fn boundary(mut self, b: Boundary) -> Self {
+    self.boundary = b;
+    self
+  }
+
+  /// How input bits are turned into moves, [`Diagonal`] by default
+  ///
+  /// [`Diagonal`]: ./struct.Diagonal.html
+  pub fn move_rule<R: MoveRule + 'static>(self, r: R) -> Self {
+    self.rule(Arc::new(r))
+  }
+
+  pub(crate) fn rule(mut self, r: Arc<dyn MoveRule>) -> Self {
+    self.rule = r;
+    self
+  }
+
+  /// Bit order and byte reversal of the input,
+  /// [`Decoding::default()`] (same as OpenSSH) by default
+  ///
+  /// [`Decoding::default()`]: ./struct.Decoding.html#impl-Default-for-Decoding
+  pub fn decoding(mut self, d: Decoding) -> Self {
+    self.decoding = d;
+    self
+  }
+
+  /// Validate parameters and create `BishopArt`
+  ///
+  /// # Errors
+  ///
+  /// + [`BadSize`] if size or limits are out of range
+  /// + [`BadStart`] if start position is outside of the field
+  /// + [`BadWalk`] if walk parameters are invalid
+  ///
+  /// [`BadSize`]: ../errors/enum.Error.html#variant.BadSize
+  /// [`BadStart`]: ../errors/enum.Error.html#variant.BadStart
+  /// [`BadWalk`]: ../errors/enum.Error.html#variant.BadWalk
+  pub fn build(self) -> Result<BishopArt> {
+    let (w, h) = self.size;
+    let ((min_w, min_h), (max_w, max_h)) = self.limits;
+    let (abs_w, abs_h) = Self::LIMITS_MIN;
+
+    let limits_ok = min_w >= abs_w && min_h >= abs_h && min_w <= max_w && min_h <= max_h;
+    if !limits_ok || w > max_w || h > max_h || w < min_w || h < min_h {
+      return Err(Error::BadSize {
+        wh: self.size,
+        min_wh: self.limits.0,
+        max_wh: self.limits.1,
+      });
+    }
+
+    let start = self.start.unwrap_or(((w - 1) / 2, (h - 1) / 2));
+    if start.0 >= w || start.1 >= h {
+      return Err(Error::BadStart {
+        start,
+        wh: self.size,
+      });
+    }
+
+    if self.saturation == 0 || self.saturation > VALUE_MAX as usize {
+      return Err(Error::BadWalk(format!(
+        "saturation {} must be within range: 1 - {}",
+        self.saturation, VALUE_MAX
+      )));
+    }
+
+    let bits = self.rule.bits();
+    if !(1..=8).contains(&bits) {
+      return Err(Error::BadWalk(format!(
+        "move rule takes {} bits per move, must be within range: 1 - 8",
+        bits
+      )));
+    }
+
+    if self.decoding.reverse_chunk == 0 {
+      return Err(Error::BadWalk("reverse chunk can't be empty".into()));
+    }
+
+    Ok(BishopArt {
+      field_w: w,
+      field_h: h,
+      map: Vec2D::new(w, h, 0isize),
+      max_count: self.saturation as isize,
+      boundary: self.boundary,
+      rule: self.rule,
+      decoding: self.decoding,
+      start,
+      pos: start,
+      bytes: 0,
+      chunk: vec![],
+      queue: VecDeque::new(),
+      bit_buf: 0,
+      bit_len: 0,
+      byte_moves: 0,
+      observer: ObserverSlot::default(),
+    })
+  }
+}
+
+impl Default for BishopArtBuilder {
+  /// Builder for field of default size
+  fn default() -> Self {
+    let (w, h) = DEFAULT_SIZE_WH;
+    Self::new(w, h)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_builder() {
+    assert_eq!(
+      BishopArtBuilder::default().build().unwrap().draw(),
+      BishopArt::new().draw()
+    );
+
+    let res = BishopArt::builder(600, 3)
+      .limits((2, 1), (1000, 10))
+      .start(599, 0)
+      .saturation(2)
+      .build()
+      .unwrap()
+      .chain([0xff, 0x00, 0x00, 0x00])
+      .result();
+    assert_eq!(res.start(), (599, 0));
+    assert_eq!(res.end(), Some((587, 0)));
+    // corner is visited 3 times, but counter stops at 2
+    assert_eq!(res.cell(599, 2).count(), 2);
+
+    match BishopArt::builder(4, 600).build() {
+      Err(Error::BadSize { wh, min_wh, max_wh }) => {
+        assert_eq!((wh, min_wh, max_wh), ((4, 600), (5, 5), (500, 500)))
+      }
+      r => panic!("{:?}", r),
+    }
+    assert!(matches!(
+      BishopArt::with_size(501, 5),
+      Err(Error::BadGeometry {
+        min_wh: (5, 5),
+        max_wh: (500, 500)
+      })
+    ));
+    assert!(matches!(
+      BishopArt::builder(1, 5).limits((1, 1), (9, 9)).build(),
+      Err(Error::BadSize { .. })
+    ));
+    assert!(matches!(
+      BishopArt::builder(5, 5).start(2, 5).build(),
+      Err(Error::BadStart {
+        start: (2, 5),
+        wh: (5, 5)
+      })
+    ));
+    assert!(matches!(
+      BishopArt::builder(5, 5).saturation(0).build(),
+      Err(Error::BadWalk(_))
+    ));
+  }
+}
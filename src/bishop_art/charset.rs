@@ -0,0 +1,344 @@
+use super::*;
+
+use std::str::FromStr;
+
+/// Checked chars for drawing the field, see [`DrawingOptions::chars`]
+///
+/// Chars are given in order:
+///
+/// Index  | Description             | Default          |
+/// -------|-------------------------|------------------|
+/// `0`    | Field background        | ` `              |
+/// `1..n` | Chars used for drawing  | `.o+=*BOX@%&#/^` |
+/// `n+1`  | Char for start position | `S`              |
+/// `n+2`  | Char for last position  | `E`              |
+///
+/// Each non-background char indicates how many
+/// times bishop has been on this position, the last one
+/// is used for all higher counts. Start and end chars overwrite the real value.
+///
+/// Any charset of at least [`MIN_LEN`] distinct chars is accepted, but
+/// longer ones of clearly distinguishable symbols make arts easier to tell apart.
+///
+/// # Example
+///
+/// ```rust
+/// use bishop::bishop_art::Charset;
+///
+/// let c = Charset::new(" .o*SE").unwrap();
+/// assert_eq!((c.start(), c.end()), ('S', 'E'));
+/// assert!(Charset::new(" .oo*SE").is_err());
+/// ```
+///
+/// [`DrawingOptions::chars`]: ./struct.DrawingOptions.html#structfield.chars
+/// [`MIN_LEN`]: ./struct.Charset.html#associatedconstant.MIN_LEN
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Charset {
+  chars: Vec<char>,
+  width: usize,
+}
+
+impl Charset {
+  /// Background, one char for visited cells, start and end
+  pub const MIN_LEN: usize = 4;
+
+  /// Names of presets, see [`preset()`]
+  ///
+  /// [`preset()`]: ./struct.Charset.html#method.preset
+  pub const PRESETS: &'static [&'static str] =
+    &["openssh", "shading", "dots", "ascii-safe", "digits"];
+
+  /// Preset by name from [`PRESETS`]
+  ///
+  /// [`PRESETS`]: ./struct.Charset.html#associatedconstant.PRESETS
+  pub fn preset(name: &str) -> Option<Self> {
+    match name {
+      "openssh" => Some(Self::openssh()),
+      "shading" => Some(Self::shading()),
+      "dots" => Some(Self::dots()),
+      "ascii-safe" => Some(Self::ascii_safe()),
+      "digits" => Some(Self::digits()),
+      _ => None,
+    }
+  }
+
+  /// Preset of one column wide chars, checked by tests
+  fn preset_of(chars: &str) -> Self {
+    Charset {
+      chars: chars.chars().collect(),
+      width: 1,
+    }
+  }
+
+  /// [`DEFAULT_CHARS`], same as OpenSSH
+  ///
+  /// [`DEFAULT_CHARS`]: ./constant.DEFAULT_CHARS.html
+  pub fn openssh() -> Self {
+    Self::preset_of(DEFAULT_CHARS)
+  }
+
+  /// Unicode block shading ` ░▒▓█`, saturates after 4 visits
+  pub fn shading() -> Self {
+    Self::preset_of(" ░▒▓█SE")
+  }
+
+  /// Braille patterns with one more dot for each visit, up to 8
+  pub fn dots() -> Self {
+    Self::preset_of(" ⠁⠃⠇⡇⣇⣧⣷⣿SE")
+  }
+
+  /// ASCII chars growing denser with visits, ` .:o=x*#%@`
+  ///
+  /// Has no digits, no letters but `o` and `x` and none of the chars
+  /// of [`FrameStyle::ascii()`], so nothing can be confused with another
+  /// char (like `0` and `O` or `l` and `1`), with start and end or with the frame
+  ///
+  /// [`FrameStyle::ascii()`]: ./struct.FrameStyle.html#method.ascii
+  pub fn ascii_safe() -> Self {
+    Self::preset_of(" .:o=x*#%@SE")
+  }
+
+  /// Raw visit counts `1` to `9`, `+` for more
+  pub fn digits() -> Self {
+    Self::preset_of(" 123456789+SE")
+  }
+
+  /// Same charset with another start char
+  ///
+  /// # Errors
+  ///
+  /// Same as [`new()`]
+  ///
+  /// [`new()`]: ./struct.Charset.html#method.new
+  pub fn with_start(mut self, c: char) -> Result<Self> {
+    let n = self.chars.len();
+    self.chars[n - 2] = c;
+    Self::new(&self.to_string())
+  }
+
+  /// Same charset with another end char
+  ///
+  /// # Errors
+  ///
+  /// Same as [`new()`]
+  ///
+  /// [`new()`]: ./struct.Charset.html#method.new
+  pub fn with_end(mut self, c: char) -> Result<Self> {
+    let n = self.chars.len();
+    self.chars[n - 1] = c;
+    Self::new(&self.to_string())
+  }
+
+  /// Charset from `chars` in the order described above
+  ///
+  /// # Errors
+  ///
+  /// [`BadCharset`] if there are less than [`MIN_LEN`] chars, any char
+  /// is repeated (including start and end chars), is a control char
+  /// or has zero width, or chars are not all of the same width
+  ///
+  /// [`BadCharset`]: ../errors/enum.Error.html#variant.BadCharset
+  /// [`MIN_LEN`]: ./struct.Charset.html#associatedconstant.MIN_LEN
+  pub fn new(chars: &str) -> Result<Self> {
+    let chars: Vec<char> = chars.chars().collect();
+    if chars.len() < Self::MIN_LEN {
+      return Err(bad(format!(
+        "at least {} chars are needed, got {}",
+        Self::MIN_LEN,
+        chars.len()
+      )));
+    }
+
+    let n = chars.len();
+    let name = |i: usize| match i {
+      i if i == n - 1 => "end char",
+      i if i == n - 2 => "start char",
+      0 => "background char",
+      _ => "char",
+    };
+
+    let mut width = None;
+    for (i, &c) in chars.iter().enumerate() {
+      if c.is_control() {
+        return Err(bad(format!("control char {:?} at {}", c, i)));
+      }
+      match (c.width(), width) {
+        (None, _) | (Some(0), _) => {
+          return Err(bad(format!("zero-width char {:?} at {}", c, i)));
+        }
+        (Some(cw), Some((w, first))) if cw != w => {
+          return Err(bad(format!(
+            "{:?} is {} columns wide, but {:?} is {}",
+            c, cw, first, w
+          )));
+        }
+        (Some(cw), None) => width = Some((cw, c)),
+        _ => {}
+      }
+      if let Some(j) = chars[..i].iter().position(|&p| p == c) {
+        return Err(bad(format!(
+          "{} {:?} at {} is already used as {} at {}",
+          name(i),
+          c,
+          i,
+          name(j),
+          j
+        )));
+      }
+    }
+
+    Ok(Charset {
+      chars,
+      width: width.map_or(1, |w| w.0),
+    })
+  }
+
+  /// All chars in order
+  pub fn chars(&self) -> &[char] {
+    &self.chars
+  }
+
+  /// Background and visited cell chars, by visit count
+  pub fn levels(&self) -> &[char] {
+    &self.chars[..self.chars.len() - 2]
+  }
+
+  /// Char for start position
+  pub fn start(&self) -> char {
+    self.chars[self.chars.len() - 2]
+  }
+
+  /// Char for last position
+  pub fn end(&self) -> char {
+    self.chars[self.chars.len() - 1]
+  }
+
+  /// Display width of each char, `1` or `2`
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  /// Char for field value
+  pub(crate) fn cell(&self, v: isize) -> char {
+    let levels = self.levels();
+    match v {
+      VALUE_E => self.end(),
+      VALUE_S => self.start(),
+      v if (v as usize) < levels.len() => levels[v as usize],
+      _ => levels[levels.len() - 1],
+    }
+  }
+}
+
+fn bad(msg: String) -> Error {
+  Error::BadCharset(msg)
+}
+
+impl Default for Charset {
+  /// [`openssh()`](#method.openssh) preset
+  fn default() -> Self {
+    Self::openssh()
+  }
+}
+
+impl FromStr for Charset {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    Self::new(s)
+  }
+}
+
+impl fmt::Display for Charset {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.chars.iter().try_for_each(|c| write!(f, "{}", c))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_charset() {
+    let def = Charset::default();
+    assert_eq!(Charset::new(DEFAULT_CHARS).unwrap(), def);
+    assert_eq!(def.to_string(), DEFAULT_CHARS);
+    assert_eq!(def.levels().len(), 15);
+    assert_eq!((def.cell(0), def.cell(3), def.cell(100)), (' ', '+', '^'));
+    assert_eq!((def.cell(VALUE_S), def.cell(VALUE_E)), ('S', 'E'));
+
+    // wide chars take two columns per cell, frame is widened to match
+    let wide: Charset = "　一二三始終".parse().unwrap();
+    assert_eq!(wide.width(), 2);
+    let o = DrawingOptions {
+      chars: wide,
+      top_text: "wide".into(),
+      ..Default::default()
+    };
+    for art in [
+      BishopArt::with_size(5, 5).unwrap(),
+      BishopArt::builder(5, 5).move_rule(Hex).build().unwrap(),
+    ] {
+      let out = art.chain(b"abc").draw_with_opts(&o);
+      let w = out.lines().next().unwrap().width();
+      assert!(out.lines().all(|l| l.width() == w), "{}", out);
+    }
+
+    for bad in [
+      "",
+      " .S",
+      " .oo*SE",
+      " .o*SS",
+      " .o*.E",
+      " .o*S ",
+      " .\t*SE",
+      " .\u{301}*SE",
+      " .漢*SE",
+      "漢字 .SE",
+    ] {
+      assert!(
+        matches!(Charset::new(bad), Err(Error::BadCharset(_))),
+        "{:?}",
+        bad
+      );
+    }
+
+    let e = Charset::new(" .o*.E").unwrap_err().to_string();
+    assert_eq!(
+      e,
+      "Charset: start char '.' at 4 is already used as char at 1"
+    );
+  }
+
+  #[test]
+  fn test_presets() {
+    for &name in Charset::PRESETS {
+      let p = Charset::preset(name).unwrap();
+      assert_eq!(Charset::new(&p.to_string()).unwrap(), p, "{}", name);
+      assert_eq!((p.start(), p.end()), ('S', 'E'));
+    }
+    assert_eq!(Charset::preset("nope"), None);
+    // no chars of ascii frame and label brackets
+    let safe = Charset::ascii_safe();
+    assert!(safe
+      .chars()
+      .iter()
+      .all(|&c| c.is_ascii() && !"+-|[]".contains(c)));
+
+    let digits = Charset::digits();
+    let counts: String = (0..12).map(|v| digits.cell(v)).collect();
+    assert_eq!(counts, " 123456789++");
+
+    let marked = Charset::shading()
+      .with_start('◆')
+      .unwrap()
+      .with_end('◇')
+      .unwrap();
+    assert_eq!(marked.to_string(), " ░▒▓█◆◇");
+    for bad in ['░', '\n', '漢'] {
+      assert!(Charset::shading().with_end(bad).is_err());
+    }
+    assert!(Charset::dots().with_start('E').is_err());
+  }
+}
@@ -0,0 +1,346 @@
+use super::*;
+
+use std::str::FromStr;
+
+/// Order in which moves are taken from each byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitOrder {
+  /// Least significant bits first, as in OpenSSH
+  #[default]
+  Lsb,
+  /// Most significant bits first
+  Msb,
+}
+
+/// How input bytes are turned into bits for [`MoveRule`]
+///
+/// Bytes are first reversed in chunks of [`reverse_chunk`] bytes
+/// (e.g. `4` reads little-endian 32-bit words as big-endian),
+/// then each byte is split into moves in [`order`].
+/// With [`BitOrder::Msb`] the first bit read is the highest bit of the move.
+///
+/// [`MoveRule`]: ./trait.MoveRule.html
+/// [`reverse_chunk`]: ./struct.Decoding.html#structfield.reverse_chunk
+/// [`order`]: ./struct.Decoding.html#structfield.order
+/// [`BitOrder::Msb`]: ./enum.BitOrder.html#variant.Msb
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decoding {
+  pub order: BitOrder,
+
+  /// Bytes are reversed in chunks of this size, `1` keeps input as is
+  ///
+  /// Incomplete chunk is held back until more input is pushed
+  /// and reversed as is by [`BishopArt::result()`]
+  ///
+  /// [`BishopArt::result()`]: ./struct.BishopArt.html#method.result
+  pub reverse_chunk: usize,
+}
+
+impl Default for Decoding {
+  /// Decoding used by OpenSSH
+  fn default() -> Self {
+    Decoding {
+      order: BitOrder::Lsb,
+      reverse_chunk: 1,
+    }
+  }
+}
+
+/// Versioned description of all walk parameters,
+/// enough to reproduce an art exactly
+///
+/// Written and parsed as single line of `key=value` pairs
+/// after the version tag:
+///
+/// ```text
+/// bishop-v1 size=17x9 start=8,4 moves=diagonal bits=2 order=lsb reverse=1 boundary=clamp saturation=9223372036854775807
+/// ```
+///
+/// Version is increased whenever the walk of existing parameters changes
+/// or new parameters are added. Drawing options are not included.
+///
+/// # Example
+///
+/// ```rust
+/// # use bishop::*;
+/// use bishop::bishop_art::Descriptor;
+///
+/// let art = BishopArt::builder(21, 11).saturation(5).build().unwrap();
+/// let d = art.descriptor().to_string();
+///
+/// let same = d.parse::<Descriptor>().unwrap().builder().unwrap().build().unwrap();
+/// assert_eq!(same.chain(b"foo").draw(), art.chain(b"foo").draw());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Descriptor {
+  pub version: u32,
+  pub size: PosXY,
+  pub start: PosXY,
+
+  /// [`MoveRule::name()`]
+  ///
+  /// [`MoveRule::name()`]: ./trait.MoveRule.html#method.name
+  pub moves: String,
+
+  /// Step width, [`MoveRule::bits()`]
+  ///
+  /// [`MoveRule::bits()`]: ./trait.MoveRule.html#tymethod.bits
+  pub bits: u8,
+
+  pub decoding: Decoding,
+  pub boundary: Boundary,
+  pub saturation: usize,
+}
+
+impl Descriptor {
+  /// Version written by this library
+  pub const VERSION: u32 = 1;
+
+  /// Largest field size accepted by [`builder()`]
+  ///
+  /// [`builder()`]: ./struct.Descriptor.html#method.builder
+  pub const MAX_SIZE: PosXY = (1024, 1024);
+
+  /// Builder configured for this walk
+  ///
+  /// Size limits of the builder are relaxed to allow any size
+  /// up to [`MAX_SIZE`], so descriptors from untrusted sources
+  /// can't make a field of arbitrary size
+  ///
+  /// # Errors
+  ///
+  /// [`BadDescriptor`] if version is not supported, size is larger
+  /// than [`MAX_SIZE`] or move rule is not a built-in one
+  ///
+  /// [`BadDescriptor`]: ../errors/enum.Error.html#variant.BadDescriptor
+  /// [`MAX_SIZE`]: ./struct.Descriptor.html#associatedconstant.MAX_SIZE
+  pub fn builder(&self) -> Result<BishopArtBuilder> {
+    if self.version != Self::VERSION {
+      return Err(bad(format!("unsupported version {}", self.version)));
+    }
+    let (max_w, max_h) = Self::MAX_SIZE;
+    if self.size.0 > max_w || self.size.1 > max_h {
+      return Err(bad(format!(
+        "size {}x{} is larger than {}x{}",
+        self.size.0, self.size.1, max_w, max_h
+      )));
+    }
+    let rule = match rules::by_name(&self.moves) {
+      Some(r) if r.bits() == self.bits => r,
+      Some(r) => {
+        return Err(bad(format!(
+          "moves {} take {} bits, not {}",
+          self.moves,
+          r.bits(),
+          self.bits
+        )))
+      }
+      None => return Err(bad(format!("unknown moves {}", self.moves))),
+    };
+
+    let (w, h) = self.size;
+    let (x, y) = self.start;
+    Ok(
+      BishopArtBuilder::new(w, h)
+        .limits(BishopArtBuilder::LIMITS_MIN, Self::MAX_SIZE)
+        .start(x, y)
+        .saturation(self.saturation)
+        .boundary(self.boundary)
+        .decoding(self.decoding)
+        .rule(rule),
+    )
+  }
+}
+
+fn bad(msg: String) -> Error {
+  Error::BadDescriptor(msg)
+}
+
+impl fmt::Display for Descriptor {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let order = match self.decoding.order {
+      BitOrder::Lsb => "lsb",
+      BitOrder::Msb => "msb",
+    };
+    let boundary = match self.boundary {
+      Boundary::Clamp => "clamp",
+      Boundary::Wrap => "wrap",
+      Boundary::Reflect => "reflect",
+    };
+    write!(
+      f,
+      "bishop-v{} size={}x{} start={},{} moves={} bits={} order={} reverse={} boundary={} saturation={}",
+      self.version,
+      self.size.0,
+      self.size.1,
+      self.start.0,
+      self.start.1,
+      self.moves,
+      self.bits,
+      order,
+      self.decoding.reverse_chunk,
+      boundary,
+      self.saturation
+    )
+  }
+}
+
+fn parse_pair(v: &str, sep: char) -> Option<PosXY> {
+  let (a, b) = v.split_once(sep)?;
+  Some((a.parse().ok()?, b.parse().ok()?))
+}
+
+impl FromStr for Descriptor {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    let mut words = s.split_whitespace();
+    let version = words
+      .next()
+      .and_then(|v| v.strip_prefix("bishop-v"))
+      .and_then(|v| v.parse().ok())
+      .ok_or_else(|| bad("missing version tag".into()))?;
+
+    let (mut size, mut start, mut moves, mut bits) = (None, None, None, None);
+    let (mut order, mut reverse, mut boundary, mut saturation) = (None, None, None, None);
+
+    for w in words {
+      let (k, v) = w.split_once('=').unwrap_or((w, ""));
+      match k {
+        "size" => size = parse_pair(v, 'x'),
+        "start" => start = parse_pair(v, ','),
+        "moves" => moves = Some(v.to_string()).filter(|v| !v.is_empty()),
+        "bits" => bits = v.parse().ok(),
+        "order" => {
+          order = match v {
+            "lsb" => Some(BitOrder::Lsb),
+            "msb" => Some(BitOrder::Msb),
+            _ => None,
+          }
+        }
+        "reverse" => reverse = v.parse().ok(),
+        "boundary" => {
+          boundary = match v {
+            "clamp" => Some(Boundary::Clamp),
+            "wrap" => Some(Boundary::Wrap),
+            "reflect" => Some(Boundary::Reflect),
+            _ => None,
+          }
+        }
+        "saturation" => saturation = v.parse().ok(),
+        _ => return Err(bad(format!("unknown parameter {}", k))),
+      }
+    }
+
+    let missing = |k: &str| bad(format!("missing or bad parameter {}", k));
+    Ok(Descriptor {
+      version,
+      size: size.ok_or_else(|| missing("size"))?,
+      start: start.ok_or_else(|| missing("start"))?,
+      moves: moves.ok_or_else(|| missing("moves"))?,
+      bits: bits.ok_or_else(|| missing("bits"))?,
+      decoding: Decoding {
+        order: order.ok_or_else(|| missing("order"))?,
+        reverse_chunk: reverse.ok_or_else(|| missing("reverse"))?,
+      },
+      boundary: boundary.ok_or_else(|| missing("boundary"))?,
+      saturation: saturation.ok_or_else(|| missing("saturation"))?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_decoding() {
+    let path = |d: Decoding, data: &[u8]| {
+      let mut art = BishopArt::builder(5, 5).decoding(d).build().unwrap();
+      let p: Vec<_> = art.steps(data).map(|s| s.symbol).collect();
+      p
+    };
+    let msb = Decoding {
+      order: BitOrder::Msb,
+      ..Default::default()
+    };
+    assert_eq!(path(Decoding::default(), &[0xf4]), [0, 1, 3, 3]);
+    assert_eq!(path(msb, &[0xf4]), [3, 3, 1, 0]);
+
+    // 16-bit chunks, second chunk is incomplete
+    let rev = Decoding {
+      reverse_chunk: 2,
+      ..msb
+    };
+    assert_eq!(path(rev, &[0x00, 0xf4, 0xff]), [3, 3, 1, 0, 0, 0, 0, 0]);
+    let res = BishopArt::builder(5, 5)
+      .decoding(rev)
+      .build()
+      .unwrap()
+      .chain([0x00, 0xf4, 0xff]);
+    assert_eq!(
+      res.draw(),
+      BishopArt::builder(5, 5)
+        .decoding(msb)
+        .build()
+        .unwrap()
+        .chain([0xf4, 0x00, 0xff])
+        .draw()
+    );
+
+    // 3-bit moves span bytes, highest bits first
+    let mut art = BishopArt::builder(5, 5)
+      .decoding(msb)
+      .move_rule(King)
+      .build()
+      .unwrap();
+    let syms: Vec<_> = art
+      .steps(&[0b1010_0111, 0b0110_0000])
+      .map(|s| s.symbol)
+      .collect();
+    assert_eq!(syms, [0b101, 0b001, 0b110, 0b110, 0b000]);
+  }
+
+  #[test]
+  fn test_descriptor() {
+    let art = BishopArt::new();
+    let d = art.descriptor();
+    assert_eq!(
+      d.to_string(),
+      "bishop-v1 size=17x9 start=8,4 moves=diagonal bits=2 order=lsb reverse=1 \
+       boundary=clamp saturation=9223372036854775807"
+    );
+    assert_eq!(d.to_string().parse::<Descriptor>().unwrap(), d);
+
+    let s = "bishop-v1 size=3x200 start=0,199 moves=hex bits=3 order=msb reverse=4 \
+             boundary=wrap saturation=3";
+    let d: Descriptor = s.parse().unwrap();
+    let art = d.builder().unwrap().build().unwrap();
+    assert_eq!(art.descriptor().to_string(), s);
+    assert_eq!(art.chain(b"hex").result().layout(), Layout::Hex);
+
+    for bad in [
+      "size=17x9",
+      "bishop-v1 size=17x9",
+      "bishop-v1 size=17 start=8,4",
+      "bishop-v1 color=red",
+    ] {
+      assert!(matches!(
+        bad.parse::<Descriptor>(),
+        Err(Error::BadDescriptor(_))
+      ));
+    }
+    let huge = Descriptor {
+      size: (3, 1025),
+      ..d.clone()
+    };
+    assert!(matches!(huge.builder(), Err(Error::BadDescriptor(_))));
+    let v2 = Descriptor { version: 2, ..d };
+    assert!(matches!(v2.builder(), Err(Error::BadDescriptor(_))));
+    let custom = Descriptor {
+      moves: "custom".into(),
+      ..v2
+    };
+    assert!(custom.to_string().parse::<Descriptor>().is_ok());
+  }
+}
@@ -0,0 +1,535 @@
+use super::*;
+
+const ELLIPSIS: &str = "…";
+
+/// Chars of frame lines and corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameChars {
+  pub horizontal: char,
+  pub vertical: char,
+  pub top_left: char,
+  pub top_right: char,
+  pub bottom_left: char,
+  pub bottom_right: char,
+}
+
+impl FrameChars {
+  /// Chars in order: horizontal, vertical, top-left, top-right,
+  /// bottom-left and bottom-right
+  const fn new(c: [char; 6]) -> Self {
+    FrameChars {
+      horizontal: c[0],
+      vertical: c[1],
+      top_left: c[2],
+      top_right: c[3],
+      bottom_left: c[4],
+      bottom_right: c[5],
+    }
+  }
+}
+
+/// Horizontal position of a frame label
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+  Left,
+  #[default]
+  Center,
+  Right,
+}
+
+/// Text on top or bottom frame border,
+/// see [`DrawingOptions::top_labels`]
+///
+/// [`DrawingOptions::top_labels`]: ./struct.DrawingOptions.html#structfield.top_labels
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Label {
+  pub text: String,
+  pub align: Align,
+}
+
+impl Label {
+  pub fn new<S: Into<String>>(text: S, align: Align) -> Self {
+    Label {
+      text: text.into(),
+      align,
+    }
+  }
+}
+
+/// What is done with label text that doesn't fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+  /// Text is cut after the last grapheme cluster that fits
+  #[default]
+  Cut,
+  /// Text is cut and ends with `…`
+  Ellipsis,
+  /// Label is taken off the border and wrapped onto lines
+  /// above the frame for top labels and below it for bottom ones,
+  /// aligned as the label. Side captions are cut.
+  Wrap,
+}
+
+/// Look of the frame around the field, see [`DrawingOptions::frame`]
+///
+/// All chars should be one column wide,
+/// brackets may be of any width
+///
+/// [`DrawingOptions::frame`]: ./struct.DrawingOptions.html#structfield.frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameStyle {
+  /// Frame chars, no frame is drawn if `None`
+  ///
+  /// Frame texts are still drawn above and below the field,
+  /// lines without text are left out
+  pub chars: Option<FrameChars>,
+
+  /// Text before frame label, `[` by default
+  pub label_open: String,
+
+  /// Text after frame label, `]` by default
+  pub label_close: String,
+
+  /// Spaces between brackets and label text
+  pub label_padding: usize,
+
+  /// Handling of labels and captions that don't fit, [`Overflow::Cut`] by default
+  ///
+  /// [`Overflow::Cut`]: ./enum.Overflow.html#variant.Cut
+  pub overflow: Overflow,
+}
+
+impl FrameStyle {
+  fn with_chars(c: [char; 6]) -> Self {
+    FrameStyle {
+      chars: Some(FrameChars::new(c)),
+      label_open: "[".into(),
+      label_close: "]".into(),
+      label_padding: 0,
+      overflow: Overflow::Cut,
+    }
+  }
+
+  /// `+-----+`, same as OpenSSH
+  pub fn ascii() -> Self {
+    Self::with_chars(['-', '|', '+', '+', '+', '+'])
+  }
+
+  /// `┌─────┐`
+  pub fn single() -> Self {
+    Self::with_chars(['─', '│', '┌', '┐', '└', '┘'])
+  }
+
+  /// `╔═════╗`
+  pub fn double() -> Self {
+    Self::with_chars(['═', '║', '╔', '╗', '╚', '╝'])
+  }
+
+  /// `╭─────╮`
+  pub fn rounded() -> Self {
+    Self::with_chars(['─', '│', '╭', '╮', '╰', '╯'])
+  }
+
+  /// `┏━━━━━┓`
+  pub fn heavy() -> Self {
+    Self::with_chars(['━', '┃', '┏', '┓', '┗', '┛'])
+  }
+
+  /// No frame, only the field and frame texts
+  pub fn none() -> Self {
+    FrameStyle {
+      chars: None,
+      ..Self::ascii()
+    }
+  }
+
+  /// Side border char
+  pub(crate) fn side(&self) -> Option<char> {
+    self.chars.map(|c| c.vertical)
+  }
+
+  /// Text cut to `max` columns according to [`overflow`](#structfield.overflow),
+  /// its width and whether it needs ellipsis
+  ///
+  /// `None` if text doesn't fit and should be wrapped
+  fn truncate<'a>(&self, text: &'a str, max: usize) -> Option<(&'a str, usize, bool)> {
+    let (fit, fit_w) = fit_width(text, max);
+    if fit.len() == text.len() {
+      return Some((fit, fit_w, false));
+    }
+    match self.overflow {
+      Overflow::Cut => Some((fit, fit_w, false)),
+      Overflow::Ellipsis => {
+        let fit = fit_width(text, max.checked_sub(1)?).0.trim_end();
+        Some((fit, fit.width() + 1, true))
+      }
+      Overflow::Wrap => None,
+    }
+  }
+
+  /// Label with brackets and padding fitting into `w` columns, and its width
+  ///
+  /// `None` if text is empty, brackets don't fit
+  /// or nothing of text is left after truncation
+  fn label(&self, text: &str, w: usize) -> Option<(String, usize)> {
+    let pad = self.label_padding;
+    let brackets = self.label_open.width() + self.label_close.width() + pad * 2;
+    if text.is_empty() || brackets > w {
+      return None;
+    }
+
+    let (text, text_w, ellipsis) = self.truncate(text, w - brackets)?;
+    if text.is_empty() && !ellipsis {
+      return None;
+    }
+    let mut s = String::with_capacity(text.len() + brackets + 3);
+    s.push_str(&self.label_open);
+    s.extend(std::iter::repeat_n(' ', pad));
+    s.push_str(text);
+    if ellipsis {
+      s.push_str(ELLIPSIS);
+    }
+    s.extend(std::iter::repeat_n(' ', pad));
+    s.push_str(&self.label_close);
+    Some((s, text_w + brackets))
+  }
+
+  /// Labels with `align` fitting into `free` columns,
+  /// each one takes a fill column besides its own width
+  ///
+  /// Labels to wrap are pushed to `wrap`
+  fn fit<'a>(
+    &self,
+    labels: &'a [Label],
+    align: Align,
+    free: &mut usize,
+    wrap: &mut Vec<&'a Label>,
+  ) -> Vec<(String, usize)> {
+    let mut fitted = Vec::new();
+    for l in labels.iter().filter(|l| l.align == align) {
+      match free.checked_sub(1).and_then(|f| self.label(&l.text, f)) {
+        Some((s, lw)) => {
+          *free -= lw + 1;
+          fitted.push((s, lw));
+        }
+        None if self.overflow == Overflow::Wrap && !l.text.trim().is_empty() => wrap.push(l),
+        None => {}
+      }
+    }
+    fitted
+  }
+
+  /// Labels placed on border `w` columns wide as `(column, label, width)`
+  /// in order of columns, see [`DrawingOptions::top_labels`] for the rules,
+  /// and labels that are to be wrapped
+  ///
+  /// [`DrawingOptions::top_labels`]: ./struct.DrawingOptions.html#structfield.top_labels
+  pub(crate) fn place<'a>(
+    &self,
+    w: usize,
+    labels: &'a [Label],
+  ) -> (Vec<(usize, String, usize)>, Vec<&'a Label>) {
+    let mut wrap = Vec::new();
+    let mut free = w;
+    let left = self.fit(labels, Align::Left, &mut free, &mut wrap);
+    if !left.is_empty() {
+      free = free.saturating_sub(1);
+    }
+    let right = self.fit(labels, Align::Right, &mut free, &mut wrap);
+    if !right.is_empty() {
+      free = free.saturating_sub(1);
+    }
+    // no fill is needed before the first centered label
+    free += 1;
+    let center = self.fit(labels, Align::Center, &mut free, &mut wrap);
+
+    let width = |v: &[(String, usize)]| v.iter().map(|l| l.1 + 1).sum::<usize>();
+    let (left_w, right_w) = (width(&left), width(&right));
+    let mut placed = Vec::with_capacity(left.len() + center.len() + right.len());
+
+    let mut col = 0;
+    for (s, lw) in left {
+      placed.push((col + 1, s, lw));
+      col += lw + 1;
+    }
+
+    if !center.is_empty() {
+      let center_w = width(&center) - 1;
+      let lo = left_w + usize::from(left_w > 0);
+      let hi = w - right_w - usize::from(right_w > 0) - center_w;
+      let mut col = ((w - center_w) / 2).clamp(lo, hi);
+      for (s, lw) in center {
+        placed.push((col, s, lw));
+        col += lw + 1;
+      }
+    }
+
+    let mut col = w - right_w;
+    for (s, lw) in right {
+      placed.push((col, s, lw));
+      col += lw + 1;
+    }
+    (placed, wrap)
+  }
+
+  /// Caption for side border of field `h` rows high
+  /// as first row and grapheme clusters, `None` if nothing is left of it
+  ///
+  /// Clusters that are not one column wide are left out, the rest
+  /// is cut to `h` rows (ending with `…` if [`Overflow::Ellipsis`] is set)
+  /// and centered
+  ///
+  /// [`Overflow::Ellipsis`]: ./enum.Overflow.html#variant.Ellipsis
+  pub(crate) fn caption<'a>(&self, text: &'a str, h: usize) -> Option<(usize, Vec<&'a str>)> {
+    let mut cells: Vec<&str> = text.graphemes(true).filter(|g| g.width() == 1).collect();
+    if cells.len() > h {
+      cells.truncate(h);
+      if self.overflow == Overflow::Ellipsis {
+        if let Some(last) = cells.last_mut() {
+          *last = ELLIPSIS;
+        }
+      }
+    }
+    if cells.is_empty() {
+      None
+    } else {
+      Some(((h - cells.len()) / 2, cells))
+    }
+  }
+
+  /// Lines `width` columns wide with labels taken off
+  /// the border `w` columns wide, as `(column, text, width)`
+  ///
+  /// Lines are broken after whitespace if possible,
+  /// otherwise between grapheme clusters
+  pub(crate) fn wrapped(
+    &self,
+    w: usize,
+    labels: &[Label],
+    width: usize,
+  ) -> Vec<(usize, String, usize)> {
+    let mut lines = Vec::new();
+    for l in self.place(w, labels).1 {
+      let mut rest = l.text.trim();
+      while !rest.is_empty() {
+        let (mut line, _) = fit_width(rest, width);
+        if line.is_empty() {
+          // cluster wider than the whole line
+          line = rest.graphemes(true).next().unwrap_or(rest);
+        } else if line.len() < rest.len() && !rest[line.len()..].starts_with(char::is_whitespace) {
+          if let Some(i) = line.rfind(char::is_whitespace).filter(|&i| i > 0) {
+            line = &line[..i];
+          }
+        }
+        rest = rest[line.len()..].trim_start();
+
+        let line = line.trim_end();
+        let lw = line.width();
+        let fill = width.saturating_sub(lw);
+        let col = match l.align {
+          Align::Left => 0,
+          Align::Center => fill / 2,
+          Align::Right => fill,
+        };
+        lines.push((col, line.to_string(), lw));
+      }
+    }
+    lines
+  }
+
+  /// Top or bottom border with labels for field `w` columns wide,
+  /// `None` if there is neither frame nor label
+  pub(crate) fn border(&self, top: bool, w: usize, labels: &[Label]) -> Option<String> {
+    let (placed, _) = self.place(w, labels);
+    let (fill, corners) = match self.chars {
+      Some(c) if top => (c.horizontal, Some((c.top_left, c.top_right))),
+      Some(c) => (c.horizontal, Some((c.bottom_left, c.bottom_right))),
+      None if !placed.is_empty() => (' ', None),
+      None => return None,
+    };
+
+    let mut s = String::with_capacity(w * fill.len_utf8() + 8);
+    s.extend(corners.map(|c| c.0));
+    let mut col = 0;
+    for (c, label, lw) in placed {
+      s.extend(std::iter::repeat_n(fill, c - col));
+      s.push_str(&label);
+      col = c + lw;
+    }
+    s.extend(std::iter::repeat_n(fill, w - col));
+    s.extend(corners.map(|c| c.1));
+    Some(s)
+  }
+}
+
+impl Default for FrameStyle {
+  /// ASCII frame
+  fn default() -> Self {
+    Self::ascii()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_frame_styles() {
+    let res = BishopArt::with_size(5, 5).unwrap().result();
+    let draw = |frame: FrameStyle, top: &str| {
+      let o = DrawingOptions {
+        frame,
+        top_text: top.into(),
+        ..Default::default()
+      };
+      res.draw_with_opts(&o)
+    };
+
+    assert_eq!(draw(FrameStyle::ascii(), ""), res.draw());
+    assert_eq!(
+      draw(FrameStyle::rounded(), "ab"),
+      "╭[ab]─╮\n│     │\n│     │\n│  E  │\n│     │\n│     │\n╰─────╯\n"
+    );
+    assert_eq!(
+      draw(FrameStyle::none(), ""),
+      "     \n     \n  E  \n     \n     \n"
+    );
+    assert_eq!(draw(FrameStyle::none(), "x").lines().next(), Some(" [x] "));
+
+    // every line is as wide as the top one
+    for f in [
+      FrameStyle::single(),
+      FrameStyle::double(),
+      FrameStyle::heavy(),
+    ] {
+      let out = draw(f, "漢字 label");
+      let widths: Vec<_> = out.lines().map(|l| l.width()).collect();
+      assert!(widths.iter().all(|&w| w == 7), "{}", out);
+    }
+
+    // padded label doesn't fit, brackets don't fit at all
+    let padded = FrameStyle {
+      label_open: "<<".into(),
+      label_close: ">>".into(),
+      label_padding: 1,
+      ..FrameStyle::heavy()
+    };
+    let abc = [Label::new("abc", Align::Center)];
+    assert_eq!(padded.border(true, 7, &abc), Some("┏<< a >>┓".into()));
+    assert_eq!(padded.border(false, 5, &abc), Some("┗━━━━━┛".into()));
+  }
+
+  #[test]
+  fn test_labels() {
+    let ascii = FrameStyle::ascii();
+    let border = |w, labels: &[(&str, Align)]| {
+      let l: Vec<_> = labels.iter().map(|&(t, a)| Label::new(t, a)).collect();
+      ascii.border(true, w, &l).unwrap()
+    };
+    use Align::*;
+
+    assert_eq!(
+      border(19, &[("RSA", Left), ("2048", Right), ("c", Center)]),
+      "+-[RSA]--[c]-[2048]-+"
+    );
+    // groups keep their order, centered ones keep off the others
+    assert_eq!(
+      border(
+        26,
+        &[
+          ("a", Left),
+          ("b", Left),
+          ("long label", Center),
+          ("c", Right)
+        ]
+      ),
+      "+-[a]-[b]-[long label]-[c]-+"
+    );
+    assert_eq!(
+      border(17, &[("a", Right), ("b", Right)]),
+      "+---------[a]-[b]-+"
+    );
+    // left labels take space first, the rest is truncated or left out
+    assert_eq!(
+      border(12, &[("c", Center), ("xyz long", Right), ("ab", Left)]),
+      "+-[ab]-[xyz]-+"
+    );
+    assert_eq!(
+      border(9, &[("very long name", Left), ("xyz", Right)]),
+      "+-[very l]+"
+    );
+    assert_eq!(border(3, &[("", Left), ("ab", Center)]), "+[a]+");
+
+    // captions are centered on the side borders
+    let res = BishopArt::with_size(5, 5).unwrap().result();
+    let o = DrawingOptions {
+      top_labels: vec![Label::new("L", Left)],
+      bottom_labels: vec![Label::new("R", Right)],
+      left_caption: "ab\u{301}".into(),
+      right_caption: "漢long".into(),
+      ..Default::default()
+    };
+    assert_eq!(
+      res.draw_with_opts(&o),
+      "+-[L]-+\n|     l\na     o\nb\u{301}  E  n\n|     g\n|     |\n+-[R]-+\n"
+    );
+    let none = DrawingOptions {
+      frame: FrameStyle::none(),
+      ..o
+    };
+    let out = res.draw_with_opts(&none);
+    assert!(out.lines().all(|l| l.width() == 7), "{}", out);
+  }
+
+  #[test]
+  fn test_overflow() {
+    use Overflow::*;
+    let border = |overflow, text: &str, w| {
+      let f = FrameStyle {
+        overflow,
+        ..FrameStyle::ascii()
+      };
+      f.border(true, w, &[Label::new(text, Align::Left)]).unwrap()
+    };
+
+    // emoji sequences, combining marks and wide chars are never split
+    assert_eq!(border(Cut, "a👨‍👩‍👧b", 6), "+-[a👨‍👩‍👧]+");
+    assert_eq!(border(Cut, "e\u{301}e\u{301}x", 5), "+-[e\u{301}e\u{301}]+");
+    assert_eq!(border(Cut, "漢字", 5), "+-[漢]+");
+    assert_eq!(border(Cut, "漢字", 4), "+----+");
+
+    assert_eq!(border(Ellipsis, "abcdef", 6), "+-[ab…]+");
+    assert_eq!(border(Ellipsis, "a bcd", 6), "+-[a…]-+");
+    assert_eq!(border(Ellipsis, "漢字", 5), "+-[…]-+");
+    assert_eq!(border(Ellipsis, "🇺🇦🇺🇦", 6), "+-[🇺🇦…]+");
+    assert_eq!(border(Ellipsis, "abc", 6), "+-[abc]+");
+
+    let ellipsis = FrameStyle {
+      overflow: Ellipsis,
+      ..FrameStyle::ascii()
+    };
+    assert_eq!(
+      ellipsis.caption("abcdefg", 5),
+      Some((0, vec!["a", "b", "c", "d", "…"]))
+    );
+
+    // wrapped labels leave the border for lines above and below the frame
+    let res = BishopArt::with_size(9, 5).unwrap().result();
+    let o = DrawingOptions {
+      top_text: "a long comment here".into(),
+      top_labels: vec![Label::new("RSA", Align::Left)],
+      bottom_labels: vec![Label::new("SHA256:abcdef", Align::Right)],
+      frame: FrameStyle {
+        overflow: Wrap,
+        ..FrameStyle::ascii()
+      },
+      ..Default::default()
+    };
+    assert_eq!(
+      res.draw_with_opts(&o),
+      "  a long   \n  comment  \n   here    \n+-[RSA]---+\n\
+       |         |\n|         |\n|    E    |\n|         |\n|         |\n\
+       +---------+\nSHA256:abcd\n         ef\n"
+    );
+  }
+}
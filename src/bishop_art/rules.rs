@@ -0,0 +1,304 @@
+use super::*;
+
+/// How field cells are arranged when drawing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+  /// Rectangular grid
+  #[default]
+  Square,
+  /// Odd rows are shifted right by half a cell, so each cell
+  /// touches six others (see [`Hex`])
+  ///
+  /// [`Hex`]: ./struct.Hex.html
+  Hex,
+}
+
+/// Rule turning input bits into bishop's moves
+///
+/// Each move consumes [`bits()`] bits of input (step width), taken from
+/// each byte in the order set by [`Decoding`]. If `bits()` doesn't divide 8,
+/// moves span byte boundaries and leftover bits wait for the next byte.
+///
+/// Moves going through the wall are handled by [`Boundary`],
+/// so rules only say where the bishop wants to go.
+///
+/// [`bits()`]: ./trait.MoveRule.html#tymethod.bits
+/// [`Decoding`]: ./struct.Decoding.html
+/// [`Boundary`]: ./enum.Boundary.html
+pub trait MoveRule: fmt::Debug + Send + Sync {
+  /// Input bits consumed by one move, `1..=8`
+  fn bits(&self) -> u8;
+
+  /// Name used in [`Descriptor`], `custom` by default
+  ///
+  /// [`Descriptor`]: ./struct.Descriptor.html
+  fn name(&self) -> &str {
+    "custom"
+  }
+
+  /// Move `(dx, dy)` for `symbol` (`0..2^bits()`) made from `pos`
+  fn delta(&self, symbol: u8, pos: PosXY) -> (isize, isize);
+
+  /// How cells are drawn, [`Layout::Square`] by default
+  ///
+  /// [`Layout::Square`]: ./enum.Layout.html#variant.Square
+  fn layout(&self) -> Layout {
+    Layout::Square
+  }
+}
+
+/// Moves along one axis at a time, 1 bit per move
+///
+/// From cells with even `x + y` the bishop moves horizontally,
+/// from odd ones vertically, so unless the wall is hit axes alternate.
+/// Set bit moves right or down, unset bit moves left or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stairs;
+
+impl MoveRule for Stairs {
+  fn bits(&self) -> u8 {
+    1
+  }
+
+  fn name(&self) -> &str {
+    "stairs"
+  }
+
+  fn delta(&self, s: u8, (x, y): PosXY) -> (isize, isize) {
+    let v = bit_v(bit_set_le(s, 7));
+    if (x + y) % 2 == 0 {
+      (v, 0)
+    } else {
+      (0, v)
+    }
+  }
+}
+
+/// OpenSSH diagonal moves, 2 bits per move
+///
+/// Bit `0` of the pair moves right if set (left otherwise),
+/// bit `1` moves down if set (up otherwise)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Diagonal;
+
+impl MoveRule for Diagonal {
+  fn bits(&self) -> u8 {
+    2
+  }
+
+  fn name(&self) -> &str {
+    "diagonal"
+  }
+
+  fn delta(&self, s: u8, _: PosXY) -> (isize, isize) {
+    (bit_v(bit_set_le(s, 7)), bit_v(bit_set_le(s, 6)))
+  }
+}
+
+/// Moves to one of 8 neighbours, 3 bits per move
+///
+/// Symbols go clockwise from up-left: `0` up-left, `1` up, `2` up-right,
+/// `3` right, `4` down-right, `5` down, `6` down-left, `7` left.
+/// Walk is denser than [`Diagonal`] since all cells are reachable.
+///
+/// [`Diagonal`]: ./struct.Diagonal.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct King;
+
+#[rustfmt::skip]
+const KING_MOVES: [(isize, isize); 8] = [
+  (-1, -1), (0, -1), (1, -1), (1, 0),
+  (1, 1), (0, 1), (-1, 1), (-1, 0),
+];
+
+impl MoveRule for King {
+  fn bits(&self) -> u8 {
+    3
+  }
+
+  fn name(&self) -> &str {
+    "king"
+  }
+
+  fn delta(&self, s: u8, _: PosXY) -> (isize, isize) {
+    KING_MOVES[s as usize & 7]
+  }
+}
+
+/// Chess knight jumps, 3 bits per move
+///
+/// Symbols go clockwise from `0` = `(1, -2)` (two up, one right).
+/// Long jumps spread the walk over the field,
+/// making arts sparser but easier to tell apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Knight;
+
+#[rustfmt::skip]
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+  (1, -2), (2, -1), (2, 1), (1, 2),
+  (-1, 2), (-2, 1), (-2, -1), (-1, -2),
+];
+
+impl MoveRule for Knight {
+  fn bits(&self) -> u8 {
+    3
+  }
+
+  fn name(&self) -> &str {
+    "knight"
+  }
+
+  fn delta(&self, s: u8, _: PosXY) -> (isize, isize) {
+    KNIGHT_MOVES[s as usize & 7]
+  }
+}
+
+/// Moves on hexagonal grid, 3 bits per move
+///
+/// Field is drawn with [`Layout::Hex`]: odd rows are shifted right,
+/// so each cell has six neighbours. Bits `0` and `1` are used
+/// as in [`Diagonal`] (right and down) for the four diagonal neighbours,
+/// set bit `2` moves horizontally instead, ignoring bit `1`.
+/// Horizontal moves are therefore twice as likely as each diagonal one.
+///
+/// [`Layout::Hex`]: ./enum.Layout.html#variant.Hex
+/// [`Diagonal`]: ./struct.Diagonal.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Hex;
+
+impl MoveRule for Hex {
+  fn bits(&self) -> u8 {
+    3
+  }
+
+  fn name(&self) -> &str {
+    "hex"
+  }
+
+  fn delta(&self, s: u8, (_, y): PosXY) -> (isize, isize) {
+    let right = bit_set_le(s, 7);
+    if bit_set_le(s, 5) {
+      return (bit_v(right), 0);
+    }
+    // neighbours above and below are at `x - 1` and `x` in even rows,
+    // and at `x` and `x + 1` in odd ones
+    let dx = match (right, y % 2 == 1) {
+      (false, false) => -1,
+      (true, true) => 1,
+      _ => 0,
+    };
+    (dx, bit_v(bit_set_le(s, 6)))
+  }
+
+  fn layout(&self) -> Layout {
+    Layout::Hex
+  }
+}
+
+/// Whole nibble per move, 4 bits
+///
+/// Bits `0-1` and `2-3` are horizontal and vertical
+/// distance: `-2`, `-1`, `1` or `2` for values `0` to `3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Nibble;
+
+impl MoveRule for Nibble {
+  fn bits(&self) -> u8 {
+    4
+  }
+
+  fn name(&self) -> &str {
+    "nibble"
+  }
+
+  fn delta(&self, s: u8, _: PosXY) -> (isize, isize) {
+    const D: [isize; 4] = [-2, -1, 1, 2];
+    (D[s as usize & 3], D[(s as usize >> 2) & 3])
+  }
+}
+
+/// Built-in rule with [`name()`]
+///
+/// [`name()`]: ./trait.MoveRule.html#method.name
+pub(crate) fn by_name(name: &str) -> Option<Arc<dyn MoveRule>> {
+  let r: Arc<dyn MoveRule> = match name {
+    "stairs" => Arc::new(Stairs),
+    "diagonal" => Arc::new(Diagonal),
+    "king" => Arc::new(King),
+    "knight" => Arc::new(Knight),
+    "hex" => Arc::new(Hex),
+    "nibble" => Arc::new(Nibble),
+    _ => return None,
+  };
+  Some(r)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rules() {
+    let diag: Vec<_> = (0..4).map(|s| Diagonal.delta(s, (0, 0))).collect();
+    assert_eq!(diag, [(-1, -1), (1, -1), (-1, 1), (1, 1)]);
+
+    // every king, knight and nibble move is distinct
+    for r in [&King as &dyn MoveRule, &Knight, &Nibble] {
+      let n = 1 << r.bits();
+      let mut d: Vec<_> = (0..n).map(|s| r.delta(s, (0, 0))).collect();
+      d.sort();
+      d.dedup();
+      assert_eq!(d.len(), n as usize);
+      assert_eq!(by_name(r.name()).unwrap().bits(), r.bits());
+    }
+
+    let stairs: Vec<_> = [(0, (2, 2)), (1, (2, 2)), (1, (2, 3))]
+      .iter()
+      .map(|&(s, p)| Stairs.delta(s, p))
+      .collect();
+    assert_eq!(stairs, [(-1, 0), (1, 0), (0, 1)]);
+
+    // six hex neighbours in both row parities
+    let even: Vec<_> = (0..8).map(|s| Hex.delta(s, (3, 2))).collect();
+    let odd: Vec<_> = (0..8).map(|s| Hex.delta(s, (3, 3))).collect();
+    assert_eq!(
+      even,
+      [
+        (-1, -1),
+        (0, -1),
+        (-1, 1),
+        (0, 1),
+        (-1, 0),
+        (1, 0),
+        (-1, 0),
+        (1, 0)
+      ]
+    );
+    assert_eq!(
+      odd,
+      [
+        (0, -1),
+        (1, -1),
+        (0, 1),
+        (1, 1),
+        (-1, 0),
+        (1, 0),
+        (-1, 0),
+        (1, 0)
+      ]
+    );
+
+    let res = BishopArt::builder(5, 5)
+      .move_rule(Knight)
+      .build()
+      .unwrap()
+      .chain([0b1101_0000])
+      .result();
+    // (2, 2) -> (3, 0) -> (5, 1) clamped to (4, 1), 2 bits are left
+    assert_eq!(res.end(), Some((4, 1)));
+    assert_eq!(
+      res.cells().iter_xy().map(|(_, c)| c.count()).sum::<usize>(),
+      2
+    );
+  }
+}
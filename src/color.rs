@@ -0,0 +1,391 @@
+use std::env;
+
+const RESET: &str = "\x1b[0m";
+
+/// Terminal color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+  /// One of 16 basic colors: `0..=7` are normal, `8..=15` are bright
+  Ansi16(u8),
+  /// Color from 256-color palette
+  Ansi256(u8),
+  /// 24-bit color
+  Rgb(u8, u8, u8),
+}
+
+/// Levels of 6x6x6 color cube in 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Basic colors as in xterm's default palette
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+  (0, 0, 0),
+  (205, 0, 0),
+  (0, 205, 0),
+  (205, 205, 0),
+  (0, 0, 238),
+  (205, 0, 205),
+  (0, 205, 205),
+  (229, 229, 229),
+  (127, 127, 127),
+  (255, 0, 0),
+  (0, 255, 0),
+  (255, 255, 0),
+  (92, 92, 255),
+  (255, 0, 255),
+  (0, 255, 255),
+  (255, 255, 255),
+];
+
+impl Color {
+  /// RGB value of color, palette colors are taken from xterm's defaults
+  pub fn rgb(self) -> (u8, u8, u8) {
+    match self {
+      Color::Ansi16(c) | Color::Ansi256(c) if c < 16 => BASIC_RGB[c as usize],
+      Color::Ansi16(c) => BASIC_RGB[(c & 15) as usize],
+      Color::Ansi256(c) if c < 232 => {
+        let c = (c - 16) as usize;
+        (
+          CUBE_LEVELS[c / 36],
+          CUBE_LEVELS[c / 6 % 6],
+          CUBE_LEVELS[c % 6],
+        )
+      }
+      Color::Ansi256(c) => {
+        let g = 8 + (c - 232) * 10;
+        (g, g, g)
+      }
+      Color::Rgb(r, g, b) => (r, g, b),
+    }
+  }
+
+  /// Color in `#rrggbb` notation
+  pub fn hex(self) -> String {
+    let (r, g, b) = self.rgb();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+  }
+
+  /// SGR parameters for setting foreground color
+  fn fg_sgr(self) -> String {
+    match self {
+      Color::Ansi16(c) if c < 8 => format!("{}", 30 + c),
+      Color::Ansi16(c) => format!("{}", 90 + (c & 7)),
+      Color::Ansi256(c) => format!("38;5;{}", c),
+      Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+    }
+  }
+}
+
+/// Text style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+  /// Foreground color, terminal default if `None`
+  pub color: Option<Color>,
+  pub bold: bool,
+}
+
+impl Style {
+  /// Style with foreground color
+  pub fn fg(c: Color) -> Self {
+    Style {
+      color: Some(c),
+      bold: false,
+    }
+  }
+
+  /// Same style in bold
+  pub fn bold(self) -> Self {
+    Style { bold: true, ..self }
+  }
+
+  /// Escape sequence enabling this style, empty for default style
+  pub fn escape(&self) -> String {
+    let mut params = vec![];
+    if self.bold {
+      params.push("1".to_string());
+    }
+    if let Some(c) = self.color {
+      params.push(c.fg_sgr());
+    }
+
+    if params.is_empty() {
+      String::new()
+    } else {
+      format!("\x1b[{}m", params.join(";"))
+    }
+  }
+}
+
+/// Number of colors supported by terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+  Ansi16,
+  Ansi256,
+  TrueColor,
+}
+
+impl ColorDepth {
+  /// Guess color depth from `$COLORTERM` and `$TERM`
+  pub fn from_env() -> Self {
+    let var = |n| env::var(n).unwrap_or_default();
+    match (var("COLORTERM").as_str(), var("TERM")) {
+      ("truecolor", _) | ("24bit", _) => ColorDepth::TrueColor,
+      (_, t) if t.contains("256") => ColorDepth::Ansi256,
+      _ => ColorDepth::Ansi16,
+    }
+  }
+}
+
+/// Colors for drawing with ANSI escape sequences (see [`DrawingOptions`])
+///
+/// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorOptions {
+  /// Colors for visit counts: first color is used for cells visited once,
+  /// last color is used for all counts above palette length
+  pub palette: Vec<Color>,
+
+  /// Style of start position
+  pub start: Style,
+
+  /// Style of last position
+  pub end: Style,
+
+  /// Style of frame borders and text
+  pub frame: Style,
+}
+
+/// Hue gradient from blue (`240°`) to red (`0°`) with full saturation
+fn gradient(n: usize) -> Vec<Color> {
+  (0..n)
+    .map(|i| {
+      let h = 240.0 * (1.0 - i as f64 / (n - 1) as f64) / 60.0;
+      let x = ((1.0 - (h % 2.0 - 1.0).abs()) * 255.0).round() as u8;
+      match h as u8 {
+        0 => Color::Rgb(255, x, 0),
+        1 => Color::Rgb(x, 255, 0),
+        2 => Color::Rgb(0, 255, x),
+        3 => Color::Rgb(0, x, 255),
+        _ => Color::Rgb(x, 0, 255),
+      }
+    })
+    .collect()
+}
+
+impl ColorOptions {
+  /// Default palette and styles for given color depth
+  pub fn with_depth(d: ColorDepth) -> Self {
+    match d {
+      ColorDepth::Ansi16 => ColorOptions {
+        palette: [4, 6, 2, 3, 1, 5, 12, 14, 10, 11, 9, 13]
+          .iter()
+          .map(|&c| Color::Ansi16(c))
+          .collect(),
+        start: Style::fg(Color::Ansi16(15)).bold(),
+        end: Style::fg(Color::Ansi16(13)).bold(),
+        frame: Style::fg(Color::Ansi16(8)),
+      },
+      ColorDepth::Ansi256 => ColorOptions {
+        palette: [
+          21, 27, 33, 39, 45, 51, 49, 47, 46, 118, 190, 226, 220, 214, 208, 196,
+        ]
+        .iter()
+        .map(|&c| Color::Ansi256(c))
+        .collect(),
+        start: Style::fg(Color::Ansi256(231)).bold(),
+        end: Style::fg(Color::Ansi256(201)).bold(),
+        frame: Style::fg(Color::Ansi256(244)),
+      },
+      ColorDepth::TrueColor => ColorOptions {
+        palette: gradient(16),
+        start: Style::fg(Color::Rgb(255, 255, 255)).bold(),
+        end: Style::fg(Color::Rgb(255, 0, 255)).bold(),
+        frame: Style::fg(Color::Rgb(128, 128, 128)),
+      },
+    }
+  }
+}
+
+impl ColorOptions {
+  /// Style for part of the art, `None` for background
+  pub(crate) fn style(&self, part: Part) -> Option<Style> {
+    match part {
+      Part::Frame => Some(self.frame),
+      Part::Start => Some(self.start),
+      Part::End => Some(self.end),
+      Part::Visits(v) => self
+        .palette
+        .get((v - 1).min(self.palette.len().saturating_sub(1)))
+        .map(|&c| Style::fg(c)),
+      Part::Background => None,
+    }
+  }
+}
+
+impl Default for ColorOptions {
+  /// 256-color palette
+  fn default() -> Self {
+    ColorOptions::with_depth(ColorDepth::Ansi256)
+  }
+}
+
+/// Part of drawn art
+#[derive(Clone, Copy)]
+pub(crate) enum Part {
+  Frame,
+  Background,
+  Visits(usize),
+  Start,
+  End,
+}
+
+/// String builder emitting escape sequences only when style changes
+pub(crate) struct Painter {
+  out: String,
+  /// Escapes for frame, start, end and palette, empty if colors are disabled
+  escapes: Option<(String, String, String, Vec<String>)>,
+  current: String,
+}
+
+impl Painter {
+  pub(crate) fn new(cap: usize, c: Option<&ColorOptions>) -> Self {
+    Painter {
+      out: String::with_capacity(cap),
+      escapes: c.map(|c| {
+        (
+          c.frame.escape(),
+          c.start.escape(),
+          c.end.escape(),
+          c.palette.iter().map(|&p| Style::fg(p).escape()).collect(),
+        )
+      }),
+      current: String::new(),
+    }
+  }
+
+  fn set(&mut self, part: Part) {
+    let (frame, start, end, palette) = match &self.escapes {
+      Some(e) => e,
+      None => return,
+    };
+
+    let e = match part {
+      Part::Frame => frame,
+      Part::Start => start,
+      Part::End => end,
+      Part::Visits(v) if !palette.is_empty() => &palette[(v - 1).min(palette.len() - 1)],
+      Part::Visits(_) | Part::Background => "",
+    };
+
+    if *e != self.current {
+      if !self.current.is_empty() {
+        self.out.push_str(RESET);
+      }
+      self.out.push_str(e);
+      self.current = e.to_string();
+    }
+  }
+
+  pub(crate) fn push(&mut self, part: Part, c: char) {
+    self.set(part);
+    self.out.push(c);
+  }
+
+  /// Reset style and start new line
+  pub(crate) fn newline(&mut self) {
+    if !self.current.is_empty() {
+      self.out.push_str(RESET);
+      self.current.clear();
+    }
+    self.out.push('\n');
+  }
+
+  pub(crate) fn finish(self) -> String {
+    self.out
+  }
+}
+
+/// Remove ANSI escape sequences (CSI) from string
+pub fn strip_escapes(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  let mut chars = s.chars();
+
+  while let Some(c) = chars.next() {
+    if c != '\x1b' {
+      out.push(c);
+      continue;
+    }
+    if chars.next() == Some('[') {
+      for f in &mut chars {
+        if ('\x40'..='\x7e').contains(&f) {
+          break;
+        }
+      }
+    }
+  }
+
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bishop_art::{BishopArt, DrawingOptions};
+
+  #[test]
+  fn test_escapes() {
+    assert_eq!(Style::fg(Color::Ansi16(3)).escape(), "\x1b[33m");
+    assert_eq!(Style::fg(Color::Ansi16(9)).bold().escape(), "\x1b[1;91m");
+    assert_eq!(Style::fg(Color::Ansi256(208)).escape(), "\x1b[38;5;208m");
+    assert_eq!(Style::fg(Color::Rgb(1, 2, 3)).escape(), "\x1b[38;2;1;2;3m");
+    assert_eq!(Style::default().escape(), "");
+
+    assert_eq!(Color::Ansi256(196).hex(), "#ff0000");
+    assert_eq!(Color::Ansi256(244).hex(), "#808080");
+    assert_eq!(Color::Ansi16(12).hex(), "#5c5cff");
+
+    let g = gradient(5);
+    assert_eq!(g[0], Color::Rgb(0, 0, 255));
+    assert_eq!(g[2], Color::Rgb(0, 255, 0));
+    assert_eq!(g[4], Color::Rgb(255, 0, 0));
+  }
+
+  #[test]
+  fn test_colored_draw() {
+    let res = BishopArt::new().chain(b"colors").result();
+
+    for &d in &[
+      ColorDepth::Ansi16,
+      ColorDepth::Ansi256,
+      ColorDepth::TrueColor,
+    ] {
+      let opts = DrawingOptions {
+        top_text: "top".to_string(),
+        color: Some(ColorOptions::with_depth(d)),
+        ..Default::default()
+      };
+      let plain = DrawingOptions {
+        top_text: "top".to_string(),
+        ..Default::default()
+      };
+
+      let colored = res.draw_with_opts(&opts);
+      assert_ne!(colored, res.draw_with_opts(&plain));
+      assert_eq!(strip_escapes(&colored), res.draw_with_opts(&plain));
+      assert!(colored.lines().all(|l| l.ends_with(RESET)));
+    }
+
+    let c = ColorOptions {
+      palette: vec![],
+      start: Style::default(),
+      end: Style::default(),
+      frame: Style::fg(Color::Ansi16(1)),
+    };
+    let out = res.draw_with_opts(&DrawingOptions {
+      color: Some(c),
+      ..Default::default()
+    });
+    let first = out.lines().nth(1).unwrap();
+    assert!(first.starts_with("\x1b[31m|\x1b[0m"));
+    assert!(first.ends_with("\x1b[31m|\x1b[0m"));
+  }
+}
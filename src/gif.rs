@@ -0,0 +1,381 @@
+use crate::animate;
+use crate::bishop_art::{BishopArt, BishopResult, DrawingOptions};
+use crate::errors::{Error, Result};
+use crate::raster::{self, RasterOptions};
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Maximum LZW code width allowed by GIF
+const MAX_CODE_BITS: u8 = 12;
+
+/// Options for animated GIF
+#[derive(Debug, Clone)]
+pub struct GifOptions {
+  /// Bytes of input pushed to field between frames
+  pub step_bytes: usize,
+
+  /// Delay between frames in hundredths of a second
+  pub delay: u16,
+
+  /// How long finished field is shown before animation loops,
+  /// in hundredths of a second
+  pub end_delay: u16,
+
+  /// Drawing mode, cell size and colors of frames
+  pub raster: RasterOptions,
+}
+
+impl Default for GifOptions {
+  fn default() -> Self {
+    GifOptions {
+      step_bytes: 1,
+      delay: 10,
+      end_delay: 200,
+      raster: RasterOptions::default(),
+    }
+  }
+}
+
+/// Push `data` to field `step_bytes` bytes at a time and collect fields
+///
+/// First field is the initial one, each next one has one more step of data
+/// and the last one is the finished field with start and end positions
+/// (same as [`BishopArt::result()`]), empty data gives only the last one
+///
+/// # Panics
+///
+/// Panics if `step_bytes` is zero
+///
+/// [`BishopArt::result()`]: ../bishop_art/struct.BishopArt.html#method.result
+pub fn walk(art: BishopArt, data: &[u8], step_bytes: usize) -> Vec<BishopResult> {
+  assert!(step_bytes > 0, "step_bytes must be positive");
+
+  animate::walk(art, |art, f| {
+    for step in data.chunks(step_bytes) {
+      art.input(step);
+      f(art);
+    }
+  })
+}
+
+/// Size of GIF frames drawn by [`draw()`] for `res`
+///
+/// # Errors
+///
+/// Same as [`raster::size()`], also [`BadImage`] if
+/// width or height doesn't fit GIF's 16-bit fields
+///
+/// [`draw()`]: ./fn.draw.html
+/// [`raster::size()`]: ../raster/fn.size.html
+/// [`BadImage`]: ../errors/enum.Error.html#variant.BadImage
+pub fn size(res: &BishopResult, o: &DrawingOptions, g: &GifOptions) -> Result<(u16, u16)> {
+  let (w, h) = raster::size(res, o, &g.raster)?;
+  match (u16::try_from(w), u16::try_from(h)) {
+    (Ok(w), Ok(h)) => Ok((w, h)),
+    _ => Err(Error::BadImage(format!(
+      "{}x{} is larger than 65535x65535 allowed by GIF",
+      w, h
+    ))),
+  }
+}
+
+/// Draw animation of pushing `data` to `art` as GIF image
+///
+/// Frames are drawn with [`raster::draw()`], so [`DrawingOptions`]
+/// are used the same way. Animation loops forever
+///
+/// # Panics
+///
+/// Same as [`walk()`]
+///
+/// # Errors
+///
+/// Same as [`size()`], checked before any frame is drawn
+///
+/// [`raster::draw()`]: ../raster/fn.draw.html
+/// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+/// [`walk()`]: ./fn.walk.html
+/// [`size()`]: ./fn.size.html
+pub fn draw(art: BishopArt, data: &[u8], o: &DrawingOptions, g: &GifOptions) -> Result<Vec<u8>> {
+  let fields = walk(art, data, g.step_bytes);
+  let (w, h) = size(&fields[0], o, g)?;
+  let frames = fields
+    .iter()
+    .map(|f| raster::draw(f, o, &g.raster))
+    .collect::<Result<Vec<_>>>()?;
+
+  // global color table with all colors of all frames
+  let mut table: Vec<(u8, u8, u8)> = vec![];
+  let mut index = HashMap::new();
+  let frames: Vec<Vec<u8>> = frames
+    .iter()
+    .map(|img| {
+      img
+        .pixels
+        .chunks(3)
+        .map(|p| {
+          let c = (p[0], p[1], p[2]);
+          *index.entry(c).or_insert_with(|| color_index(&mut table, c))
+        })
+        .collect()
+    })
+    .collect();
+
+  let bits = (1..=8).find(|&b| table.len() <= 1 << b).unwrap_or(8);
+  table.resize(1 << bits, (0, 0, 0));
+
+  let mut out = b"GIF89a".to_vec();
+  out.extend_from_slice(&w.to_le_bytes());
+  out.extend_from_slice(&h.to_le_bytes());
+  out.extend_from_slice(&[0x80 | 0x70 | (bits - 1), 0, 0]); // global table, 8-bit source
+  for &(r, g, b) in &table {
+    out.extend_from_slice(&[r, g, b]);
+  }
+
+  // NETSCAPE2.0 extension: loop forever
+  out.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+  let min_code = bits.max(2);
+  let last = frames.len() - 1;
+  for (i, f) in frames.iter().enumerate() {
+    let delay = if i == last { g.end_delay } else { g.delay };
+    out.extend_from_slice(&[0x21, 0xf9, 4, 0x04]); // graphic control, don't dispose
+    out.extend_from_slice(&delay.to_le_bytes());
+    out.extend_from_slice(&[0, 0]);
+
+    out.push(0x2c);
+    out.extend_from_slice(&[0, 0, 0, 0]);
+    out.extend_from_slice(&w.to_le_bytes());
+    out.extend_from_slice(&h.to_le_bytes());
+    out.push(0);
+
+    out.push(min_code);
+    for block in lzw_encode(f, min_code).chunks(255) {
+      out.push(block.len() as u8);
+      out.extend_from_slice(block);
+    }
+    out.push(0);
+  }
+
+  out.push(0x3b);
+  Ok(out)
+}
+
+/// Index of color in table, new colors are added until table is full,
+/// then the nearest one is used
+fn color_index(table: &mut Vec<(u8, u8, u8)>, c: (u8, u8, u8)) -> u8 {
+  if table.len() < 256 {
+    table.push(c);
+    return (table.len() - 1) as u8;
+  }
+
+  let dist = |t: &(u8, u8, u8)| {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+    d(t.0, c.0) + d(t.1, c.1) + d(t.2, c.2)
+  };
+  (0..table.len()).min_by_key(|&i| dist(&table[i])).unwrap() as u8
+}
+
+/// LSB-first packing of variable width codes
+struct BitWriter {
+  out: Vec<u8>,
+  acc: u32,
+  len: u8,
+}
+
+impl BitWriter {
+  fn write(&mut self, code: u16, bits: u8) {
+    self.acc |= u32::from(code) << self.len;
+    self.len += bits;
+    while self.len >= 8 {
+      self.out.push(self.acc as u8);
+      self.acc >>= 8;
+      self.len -= 8;
+    }
+  }
+
+  fn finish(mut self) -> Vec<u8> {
+    if self.len > 0 {
+      self.out.push(self.acc as u8);
+    }
+    self.out
+  }
+}
+
+/// GIF flavour of LZW with clear code at start and when dictionary is full
+fn lzw_encode(data: &[u8], min_code: u8) -> Vec<u8> {
+  let clear = 1u16 << min_code;
+  let eoi = clear + 1;
+
+  let mut w = BitWriter {
+    out: vec![],
+    acc: 0,
+    len: 0,
+  };
+  let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+  let mut bits = min_code + 1;
+  let mut next = eoi + 1;
+
+  w.write(clear, bits);
+
+  let mut data = data.iter();
+  let mut cur = match data.next() {
+    Some(&k) => u16::from(k),
+    None => {
+      w.write(eoi, bits);
+      return w.finish();
+    }
+  };
+
+  for &k in data {
+    if let Some(&c) = dict.get(&(cur, k)) {
+      cur = c;
+      continue;
+    }
+
+    w.write(cur, bits);
+    dict.insert((cur, k), next);
+    next += 1;
+
+    // decoder adds entries one code later, hence `>` instead of `>=`
+    if next > 1 << bits && bits < MAX_CODE_BITS {
+      bits += 1;
+    }
+    if next == 1 << MAX_CODE_BITS {
+      w.write(clear, bits);
+      dict.clear();
+      bits = min_code + 1;
+      next = eoi + 1;
+    }
+
+    cur = u16::from(k);
+  }
+
+  w.write(cur, bits);
+  if next == 1 << bits && bits < MAX_CODE_BITS {
+    bits += 1;
+  }
+  w.write(eoi, bits);
+  w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::raster::RasterMode;
+
+  /// Reference decoder following GIF spec
+  fn lzw_decode(data: &[u8], min_code: u8) -> Vec<u8> {
+    let clear = 1usize << min_code;
+    let mut pos = 0;
+    let mut read = |bits: u8| {
+      let mut v = 0;
+      for i in 0..bits as usize {
+        let b = (data[(pos + i) / 8] >> ((pos + i) % 8)) & 1;
+        v |= (b as usize) << i;
+      }
+      pos += bits as usize;
+      v
+    };
+
+    let reset = || -> Vec<Vec<u8>> { (0..clear + 2).map(|i| vec![i as u8]).collect() };
+    let mut table = reset();
+    let mut bits = min_code + 1;
+    let mut prev: Option<Vec<u8>> = None;
+    let mut out = vec![];
+
+    loop {
+      let code = read(bits);
+      if code == clear {
+        table = reset();
+        bits = min_code + 1;
+        prev = None;
+        continue;
+      }
+      if code == clear + 1 {
+        return out;
+      }
+
+      let entry = match (&prev, table.get(code)) {
+        (_, Some(e)) => e.clone(),
+        (Some(p), None) => [&p[..], &p[..1]].concat(),
+        (None, None) => panic!("bad code"),
+      };
+      out.extend_from_slice(&entry);
+      if let Some(p) = prev {
+        table.push([&p[..], &entry[..1]].concat());
+        if table.len() == 1 << bits && bits < MAX_CODE_BITS {
+          bits += 1;
+        }
+      }
+      prev = Some(entry);
+    }
+  }
+
+  #[test]
+  fn test_lzw() {
+    let mut x = 1u32;
+    let noise: Vec<u8> = (0..20000)
+      .map(|_| {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        (x % 16) as u8
+      })
+      .collect();
+    let runs: Vec<u8> = (0..50000).map(|i| (i / 300 % 4) as u8).collect();
+
+    for (data, min_code) in [(&noise, 4), (&runs, 2), (&vec![], 2), (&vec![3], 2)].iter() {
+      assert_eq!(&lzw_decode(&lzw_encode(data, *min_code), *min_code), *data);
+    }
+  }
+
+  #[test]
+  fn test_walk() {
+    let data = b"walking bishop";
+    let frames = walk(BishopArt::new(), data, 4);
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[0].field(), BishopArt::new().snapshot().field());
+    assert_eq!(
+      frames[2].field(),
+      BishopArt::new().chain(&data[..8]).snapshot().field()
+    );
+    assert_eq!(
+      frames[4].field(),
+      BishopArt::new().chain(data).result().field()
+    );
+
+    let gif = draw(
+      BishopArt::new(),
+      data,
+      &DrawingOptions::default(),
+      &GifOptions {
+        step_bytes: 4,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+    assert!(gif.ends_with(b"\x00\x3b"));
+    assert_eq!(gif.windows(3).filter(|w| w == b"\x21\xf9\x04").count(), 5);
+
+    // 500 * 140 px doesn't fit GIF's 16-bit width
+    let wide = GifOptions {
+      raster: RasterOptions {
+        mode: RasterMode::Squares,
+        cell_size: 140,
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    let art = BishopArt::with_size(500, 5).unwrap();
+    assert_eq!(
+      size(&art.clone().result(), &DrawingOptions::default(), &wide)
+        .unwrap_err()
+        .to_string(),
+      "Image: 70004x704 is larger than 65535x65535 allowed by GIF"
+    );
+    assert!(draw(art, data, &DrawingOptions::default(), &wide).is_err());
+  }
+}
@@ -0,0 +1,291 @@
+use crate::bishop_art::{BishopArt, BishopResult, DrawingOptions};
+use crate::errors::Result;
+
+pub use digest::Digest;
+
+use std::io::{self, ErrorKind, Read, Write};
+
+/// Visualizer that hashes all input and draws the digest
+///
+/// Input is pushed into hasher `D` (any [`Digest`] implementation),
+/// then digest is drawn on field when [`result()`] is called
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "sha256")] {
+/// use bishop::hashed::{HashedBishop, Sha256};
+/// use std::io::{self, Read};
+///
+/// let mut art = HashedBishop::<Sha256>::new();
+/// io::copy(&mut io::repeat(0).take(1 << 20), &mut art).unwrap();
+/// println!("{}", art.result().draw());
+/// # }
+/// ```
+///
+/// [`Digest`]: https://docs.rs/digest/0.10/digest/trait.Digest.html
+/// [`result()`]: ./struct.HashedBishop.html#method.result
+pub struct HashedBishop<D> {
+  art: BishopArt,
+  hasher: D,
+}
+
+impl<D: Digest> HashedBishop<D> {
+  /// Creates new instance with default field size
+  pub fn new() -> Self {
+    Self::from_art(BishopArt::new())
+  }
+
+  /// Creates new instance with custom field size, see [`BishopArt::with_size()`]
+  ///
+  /// [`BishopArt::with_size()`]: ../bishop_art/struct.BishopArt.html#method.with_size
+  pub fn with_size(w: usize, h: usize) -> Result<Self> {
+    Ok(Self::from_art(BishopArt::with_size(w, h)?))
+  }
+
+  /// Creates new instance drawing digest on top of provided `BishopArt`
+  pub fn from_art(art: BishopArt) -> Self {
+    HashedBishop {
+      art,
+      hasher: D::new(),
+    }
+  }
+
+  /// Push bytes for hashing
+  pub fn input<T: AsRef<[u8]>>(&mut self, i: T) {
+    self.hasher.update(i);
+  }
+
+  /// Same as [`input()`] but suitable for chaining
+  ///
+  /// [`input()`]: ./struct.HashedBishop.html#method.input
+  pub fn chain<T: AsRef<[u8]>>(mut self, i: T) -> Self {
+    self.input(i);
+    self
+  }
+
+  /// Finalize the hash and return digest with resulting field
+  pub fn finalize(self) -> (Vec<u8>, BishopResult) {
+    let digest = self.hasher.finalize().to_vec();
+    let res = self.art.chain(&digest).result();
+    (digest, res)
+  }
+
+  /// Finalize the hash and return resulting field
+  pub fn result(self) -> BishopResult {
+    self.finalize().1
+  }
+
+  /// Finalize and draw using default parameters
+  pub fn draw(self) -> String {
+    self.result().draw()
+  }
+
+  /// Finalize and draw using parameters from [`DrawingOptions`]
+  ///
+  /// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+  pub fn draw_with_opts(self, o: &DrawingOptions) -> String {
+    self.result().draw_with_opts(o)
+  }
+}
+
+impl<D: Digest> Default for HashedBishop<D> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<D: Digest> Write for HashedBishop<D> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.input(buf);
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+#[cfg(feature = "sha256")]
+pub use sha2::Sha256;
+
+#[cfg(feature = "sha512")]
+pub use sha2::Sha512;
+
+#[cfg(feature = "sha1")]
+pub use sha1::Sha1;
+
+#[cfg(feature = "md5")]
+pub use md5::Md5;
+
+#[cfg(feature = "blake2b")]
+pub use blake2::Blake2b512;
+
+#[cfg(feature = "blake3")]
+pub use blake3::Hasher as Blake3;
+
+/// Hash algorithms enabled by cargo features, for choosing at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+  #[cfg(feature = "sha256")]
+  Sha256,
+  #[cfg(feature = "sha512")]
+  Sha512,
+  #[cfg(feature = "sha1")]
+  Sha1,
+  #[cfg(feature = "md5")]
+  Md5,
+  /// BLAKE2b-512
+  #[cfg(feature = "blake2b")]
+  Blake2b,
+  #[cfg(feature = "blake3")]
+  Blake3,
+}
+
+/// Hash everything from reader with `D`
+fn read_digest<D: Digest, R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+  const READ_BUF_SIZE: usize = 8192;
+
+  let mut h = D::new();
+  let mut buf = [0u8; READ_BUF_SIZE];
+
+  loop {
+    match r.read(&mut buf) {
+      Ok(0) => break,
+      Ok(n) => h.update(&buf[..n]),
+      Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+      Err(e) => return Err(e),
+    }
+  }
+
+  Ok(h.finalize().to_vec())
+}
+
+impl Algorithm {
+  /// All enabled algorithms
+  pub const ALL: &'static [Algorithm] = &[
+    #[cfg(feature = "sha256")]
+    Algorithm::Sha256,
+    #[cfg(feature = "sha512")]
+    Algorithm::Sha512,
+    #[cfg(feature = "sha1")]
+    Algorithm::Sha1,
+    #[cfg(feature = "md5")]
+    Algorithm::Md5,
+    #[cfg(feature = "blake2b")]
+    Algorithm::Blake2b,
+    #[cfg(feature = "blake3")]
+    Algorithm::Blake3,
+  ];
+
+  /// Lowercase algorithm name
+  pub fn name(self) -> &'static str {
+    match self {
+      #[cfg(feature = "sha256")]
+      Algorithm::Sha256 => "sha256",
+      #[cfg(feature = "sha512")]
+      Algorithm::Sha512 => "sha512",
+      #[cfg(feature = "sha1")]
+      Algorithm::Sha1 => "sha1",
+      #[cfg(feature = "md5")]
+      Algorithm::Md5 => "md5",
+      #[cfg(feature = "blake2b")]
+      Algorithm::Blake2b => "blake2b",
+      #[cfg(feature = "blake3")]
+      Algorithm::Blake3 => "blake3",
+    }
+  }
+
+  /// Find enabled algorithm by name (case insensitive)
+  pub fn from_name(name: &str) -> Option<Algorithm> {
+    Self::ALL
+      .iter()
+      .copied()
+      .find(|a| a.name().eq_ignore_ascii_case(name))
+  }
+
+  /// Hash everything from reader and return digest
+  pub fn digest<R: Read>(self, r: &mut R) -> io::Result<Vec<u8>> {
+    match self {
+      #[cfg(feature = "sha256")]
+      Algorithm::Sha256 => read_digest::<Sha256, _>(r),
+      #[cfg(feature = "sha512")]
+      Algorithm::Sha512 => read_digest::<Sha512, _>(r),
+      #[cfg(feature = "sha1")]
+      Algorithm::Sha1 => read_digest::<Sha1, _>(r),
+      #[cfg(feature = "md5")]
+      Algorithm::Md5 => read_digest::<Md5, _>(r),
+      #[cfg(feature = "blake2b")]
+      Algorithm::Blake2b => read_digest::<Blake2b512, _>(r),
+      #[cfg(feature = "blake3")]
+      Algorithm::Blake3 => read_digest::<Blake3, _>(r),
+    }
+  }
+
+  /// Hash everything from reader and draw digest on top of provided `BishopArt`
+  ///
+  /// Returns digest with resulting field
+  pub fn read_art<R: Read>(self, r: &mut R, art: BishopArt) -> io::Result<(Vec<u8>, BishopResult)> {
+    let digest = self.digest(r)?;
+    let res = art.chain(&digest).result();
+    Ok((digest, res))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn check(alg: &str, digest_hex: &str) {
+    let alg = Algorithm::from_name(alg).unwrap();
+    let (digest, res) = alg
+      .read_art(&mut b"abc".as_ref(), BishopArt::new())
+      .unwrap();
+    assert_eq!(hex::encode(&digest), digest_hex);
+    assert_eq!(res.draw(), BishopArt::new().chain(&digest).draw());
+  }
+
+  #[test]
+  fn test_algorithms() {
+    #[cfg(feature = "sha256")]
+    check(
+      "SHA256",
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    );
+    #[cfg(feature = "sha1")]
+    check("sha1", "a9993e364706816aba3e25717850c26c9cd0d89d");
+    #[cfg(feature = "md5")]
+    check("md5", "900150983cd24fb0d6963f7d28e17f72");
+    #[cfg(feature = "blake3")]
+    check(
+      "blake3",
+      "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+    );
+    #[cfg(feature = "sha512")]
+    check(
+      "sha512",
+      "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+       2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    );
+    #[cfg(feature = "blake2b")]
+    check(
+      "blake2b",
+      "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+       7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    );
+  }
+
+  #[cfg(feature = "sha256")]
+  #[test]
+  fn test_write() {
+    let mut art = HashedBishop::<Sha256>::with_size(21, 11).unwrap();
+    art.write_all(b"ab").unwrap();
+    let (digest, res) = art.chain(b"c").finalize();
+
+    assert_eq!(digest, Sha256::digest(b"abc").to_vec());
+    assert_eq!(
+      res.draw(),
+      BishopArt::with_size(21, 11).unwrap().chain(&digest).draw()
+    );
+  }
+}
@@ -66,9 +66,51 @@
 /// Module that does the thing
 pub mod bishop_art;
 
-mod vec2d;
+/// Drawing with ANSI colors
+pub mod color;
 
-pub use bishop_art::{BishopArt, BishopResult, DrawingOptions};
+/// Drawing as SVG images
+pub mod svg;
+
+/// Drawing as PNG and PPM/PGM images
+pub mod raster;
+
+/// Animated GIF of the bishop's walk
+pub mod gif;
+
+/// Terminal animation and asciicast recording of the bishop's walk
+pub mod animate;
+
+/// Drawing byte-identical to OpenSSH's randomart
+pub mod openssh;
+
+/// Hashing data before visualizing
+#[cfg(any(
+  feature = "sha256",
+  feature = "sha512",
+  feature = "sha1",
+  feature = "md5",
+  feature = "blake2b",
+  feature = "blake3"
+))]
+pub mod hashed;
+
+/// Parsing OpenSSH public keys
+#[cfg(feature = "ssh")]
+pub mod ssh;
+
+/// Parsing OpenPGP public keys
+#[cfg(feature = "pgp")]
+pub mod pgp;
+
+/// Parsing X.509 certificates and CSRs
+#[cfg(feature = "x509")]
+pub mod x509;
+
+/// 2D vector used for fields
+pub mod vec2d;
+
+pub use bishop_art::{BishopArt, BishopArtBuilder, BishopResult, DrawingOptions};
 
 /// Module with local errors
 pub mod errors {
@@ -80,11 +122,50 @@ pub mod errors {
     #[error("IO: {0}")]
     IO(#[source] io::Error),
 
+    /// Field size out of default limits, see `BishopArt::with_size()`
     #[error("Field geometry must be within range: {min_wh:?} - {max_wh:?}")]
     BadGeometry {
       min_wh: (usize, usize),
       max_wh: (usize, usize),
     },
+
+    /// Field size out of builder limits, see `BishopArtBuilder::build()`
+    #[error("Field size {wh:?} must be within range: {min_wh:?} - {max_wh:?}")]
+    BadSize {
+      wh: (usize, usize),
+      min_wh: (usize, usize),
+      max_wh: (usize, usize),
+    },
+
+    #[error("Start position {start:?} is outside of {wh:?} field")]
+    BadStart {
+      start: (usize, usize),
+      wh: (usize, usize),
+    },
+
+    #[error("Walk parameters: {0}")]
+    BadWalk(String),
+
+    #[error("Algorithm descriptor: {0}")]
+    BadDescriptor(String),
+
+    #[error("Charset: {0}")]
+    BadCharset(String),
+
+    #[error("Image: {0}")]
+    BadImage(String),
+
+    #[error("SSH key: {0}")]
+    SshKey(String),
+
+    #[error("SSH agent: {0}")]
+    Agent(String),
+
+    #[error("OpenPGP: {0}")]
+    Pgp(String),
+
+    #[error("X.509: {0}")]
+    X509(String),
   }
 
   /// Local result type
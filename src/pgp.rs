@@ -0,0 +1,717 @@
+use crate::bishop_art::{BishopArt, BishopResult, DrawingOptions};
+use crate::errors::{Error, Result};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use std::fmt;
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+// signature subpacket types
+const SUB_CREATED: u8 = 2;
+const SUB_ISSUER: u8 = 16;
+const SUB_PRIMARY_USER_ID: u8 = 25;
+const SUB_ISSUER_FINGERPRINT: u8 = 33;
+
+const ARMOR_BEGIN: &str = "-----BEGIN PGP ";
+const ARMOR_END: &str = "-----END PGP ";
+
+fn _raise<T, S: Into<String>>(m: S) -> Result<T> {
+  Err(Error::Pgp(m.into()))
+}
+
+/// Known curve OIDs and names used by GnuPG
+const CURVES: &[(&[u8], &str)] = &[
+  (
+    &[0x2B, 0x06, 0x01, 0x04, 0x01, 0xDA, 0x47, 0x0F, 0x01],
+    "ed25519",
+  ),
+  (
+    &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01],
+    "cv25519",
+  ),
+  (
+    &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07],
+    "nistp256",
+  ),
+  (&[0x2B, 0x81, 0x04, 0x00, 0x22], "nistp384"),
+  (&[0x2B, 0x81, 0x04, 0x00, 0x23], "nistp521"),
+  (
+    &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x07],
+    "brainpoolP256r1",
+  ),
+  (
+    &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0B],
+    "brainpoolP384r1",
+  ),
+  (
+    &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0D],
+    "brainpoolP512r1",
+  ),
+  (&[0x2B, 0x65, 0x71], "ed448"),
+  (&[0x2B, 0x65, 0x6F], "cv448"),
+];
+
+/// Public key or subkey packet
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyPacket {
+  /// Packet version: 4, 5 or 6
+  pub version: u8,
+  /// Public key algorithm id (RFC 9580, section 9.1)
+  pub algorithm: u8,
+  /// Creation time, seconds since unix epoch
+  pub created: u32,
+  pub subkey: bool,
+  /// SHA-1 for v4 keys, SHA-256 for v5 and v6 keys
+  pub fingerprint: Vec<u8>,
+  /// Algorithm and size as printed by GnuPG (e.g. `rsa2048`, `ed25519`)
+  pub name: String,
+}
+
+/// Transferable public key: primary key with its user IDs and subkeys
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferableKey {
+  pub primary: KeyPacket,
+  pub user_ids: Vec<String>,
+  /// Index of user ID marked as primary by its self-signature
+  pub primary_user_id: Option<usize>,
+  pub subkeys: Vec<KeyPacket>,
+}
+
+/// Art for primary key or subkey fingerprint
+pub struct PgpArt {
+  pub result: BishopResult,
+  pub key: KeyPacket,
+  /// Primary user ID of the key, empty if there is none
+  pub user_id: String,
+}
+
+/// Big-endian number
+fn be(b: &[u8]) -> usize {
+  b.iter().fold(0usize, |a, &x| a << 8 | x as usize)
+}
+
+fn mpi_bits(m: &[u8]) -> Result<u32> {
+  match m {
+    [a, b, ..] => Ok(u32::from(*a) << 8 | u32::from(*b)),
+    _ => _raise("Truncated key material"),
+  }
+}
+
+/// Algorithm and size from key material
+fn key_name(algorithm: u8, material: &[u8]) -> Result<String> {
+  let curve = || -> Result<String> {
+    let ln = *material.first().unwrap_or(&0) as usize;
+    match material.get(1..=ln) {
+      Some(oid) => Ok(
+        CURVES
+          .iter()
+          .find(|(o, _)| *o == oid)
+          .map(|(_, n)| n.to_string())
+          .unwrap_or_else(|| "unknown".to_string()),
+      ),
+      None => _raise("Truncated curve OID"),
+    }
+  };
+
+  Ok(match algorithm {
+    1..=3 => format!("rsa{}", mpi_bits(material)?),
+    16 | 20 => format!("elg{}", mpi_bits(material)?),
+    17 => format!("dsa{}", mpi_bits(material)?),
+    18 | 19 | 22 => curve()?,
+    25 => "x25519".to_string(),
+    26 => "x448".to_string(),
+    27 => "ed25519".to_string(),
+    28 => "ed448".to_string(),
+    a => format!("algo{}", a),
+  })
+}
+
+impl KeyPacket {
+  /// Parse public key or subkey packet body
+  pub fn parse(body: &[u8], subkey: bool) -> Result<KeyPacket> {
+    if body.len() < 6 {
+      return _raise("Truncated key packet");
+    }
+
+    let version = body[0];
+    let created = u32::from_be_bytes([body[1], body[2], body[3], body[4]]);
+    let algorithm = body[5];
+
+    let (material, fingerprint) = match version {
+      4 => {
+        let mut h = Sha1::new();
+        h.update([0x99]);
+        h.update((body.len() as u16).to_be_bytes());
+        h.update(body);
+        (&body[6..], h.finalize().to_vec())
+      }
+      5 | 6 => {
+        let mut h = Sha256::new();
+        h.update([if version == 5 { 0x9A } else { 0x9B }]);
+        h.update((body.len() as u32).to_be_bytes());
+        h.update(body);
+        (body.get(10..).unwrap_or(&[]), h.finalize().to_vec())
+      }
+      v => return _raise(format!("Unsupported key version: {}", v)),
+    };
+
+    Ok(KeyPacket {
+      version,
+      algorithm,
+      created,
+      subkey,
+      fingerprint,
+      name: key_name(algorithm, material)?,
+    })
+  }
+
+  /// Key ID: low 64 bits of v4 fingerprint, high 64 bits of v5/v6 fingerprint
+  pub fn key_id(&self) -> &[u8] {
+    let f = &self.fingerprint;
+    match self.version {
+      4 => &f[f.len() - 8..],
+      _ => &f[..8],
+    }
+  }
+
+  /// Fingerprint as uppercase hex
+  pub fn fingerprint_hex(&self) -> String {
+    self
+      .fingerprint
+      .iter()
+      .map(|b| format!("{:02X}", b))
+      .collect()
+  }
+
+  /// Visualize key fingerprint using provided `BishopArt`
+  pub fn art_with(&self, art: BishopArt) -> BishopResult {
+    art.chain(&self.fingerprint).result()
+  }
+}
+
+impl TransferableKey {
+  /// User ID marked as primary, or the first one if none is marked
+  pub fn user_id(&self) -> Option<&str> {
+    let i = self.primary_user_id.unwrap_or(0);
+    self.user_ids.get(i).map(String::as_str)
+  }
+
+  /// Primary key followed by subkeys
+  pub fn keys(&self) -> impl Iterator<Item = &KeyPacket> {
+    std::iter::once(&self.primary).chain(self.subkeys.iter())
+  }
+
+  /// Visualize fingerprints of primary key and each subkey on fields of default size
+  pub fn arts(&self) -> Vec<PgpArt> {
+    self.arts_with(&BishopArt::new())
+  }
+
+  /// Visualize fingerprints of primary key and each subkey using copies of provided `BishopArt`
+  pub fn arts_with(&self, art: &BishopArt) -> Vec<PgpArt> {
+    let user_id = self.user_id().unwrap_or_default().to_string();
+    self
+      .keys()
+      .map(|k| PgpArt {
+        result: k.art_with(art.clone()),
+        key: k.clone(),
+        user_id: user_id.clone(),
+      })
+      .collect()
+  }
+}
+
+impl PgpArt {
+  /// Draw art using parameters from [`DrawingOptions`]
+  ///
+  /// Empty `top_text` and `bottom_text` are replaced with
+  /// user ID and key kind (`pub`/`sub`) with algorithm respectively
+  ///
+  /// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+  pub fn draw_with_opts(&self, o: &DrawingOptions) -> String {
+    let text = |t: &String, d: String| if t.is_empty() { d } else { t.clone() };
+
+    let opts = DrawingOptions {
+      top_text: text(&o.top_text, self.user_id.clone()),
+      bottom_text: text(&o.bottom_text, format!("{} {}", self.kind(), self.key.name)),
+      ..o.clone()
+    };
+
+    self.result.draw_with_opts(&opts)
+  }
+
+  /// Draw art using default parameters
+  pub fn draw(&self) -> String {
+    self.draw_with_opts(&DrawingOptions::default())
+  }
+
+  fn kind(&self) -> &'static str {
+    if self.key.subkey {
+      "sub"
+    } else {
+      "pub"
+    }
+  }
+}
+
+impl fmt::Display for PgpArt {
+  /// Formats key line as `<pub|sub> <algorithm> <fingerprint> [user ID]`
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} {} {}",
+      self.kind(),
+      self.key.name,
+      self.key.fingerprint_hex()
+    )?;
+    if !self.key.subkey && !self.user_id.is_empty() {
+      write!(f, " {}", self.user_id)?;
+    }
+    Ok(())
+  }
+}
+
+/// CRC-24 used in ASCII armor checksum
+fn crc24(data: &[u8]) -> u32 {
+  let mut crc = 0xB7_04CE_u32;
+  for &b in data {
+    crc ^= u32::from(b) << 16;
+    for _ in 0..8 {
+      crc <<= 1;
+      if crc & 0x100_0000 != 0 {
+        crc ^= 0x186_4CFB;
+      }
+    }
+  }
+  crc & 0xFF_FFFF
+}
+
+/// Decode ASCII-armored data, concatenating all armored blocks
+pub fn dearmor(text: &str) -> Result<Vec<u8>> {
+  let mut out = vec![];
+  let mut lines = text.lines().map(str::trim);
+
+  while let Some(l) = lines.next() {
+    if !l.starts_with(ARMOR_BEGIN) {
+      continue;
+    }
+
+    let mut b64 = String::new();
+    let mut crc = None;
+    let mut ended = false;
+    let mut in_headers = true;
+
+    for l in &mut lines {
+      if in_headers {
+        if l.contains(':') {
+          continue;
+        }
+        in_headers = false;
+        if l.is_empty() {
+          continue;
+        }
+      }
+
+      if l.starts_with(ARMOR_END) {
+        ended = true;
+        break;
+      } else if let Some(c) = l.strip_prefix('=') {
+        crc = Some(c.to_string());
+      } else {
+        b64.push_str(l);
+      }
+    }
+
+    if !ended {
+      return _raise("Unterminated armor block");
+    }
+
+    let data = match STANDARD.decode(&b64) {
+      Ok(d) => d,
+      Err(e) => return _raise(format!("Bad armor data: {}", e)),
+    };
+
+    if let Some(c) = crc {
+      let c = match STANDARD.decode(&c) {
+        Ok(c) if c.len() == 3 => u32::from(c[0]) << 16 | u32::from(c[1]) << 8 | u32::from(c[2]),
+        _ => return _raise("Bad armor checksum"),
+      };
+      if c != crc24(&data) {
+        return _raise("Armor checksum mismatch");
+      }
+    }
+
+    out.extend_from_slice(&data);
+  }
+
+  if out.is_empty() {
+    return _raise("No armored data found");
+  }
+
+  Ok(out)
+}
+
+/// Split binary data into `(tag, body)` packets
+fn packets(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>> {
+  let mut v = vec![];
+
+  while let Some(&hdr) = data.first() {
+    if hdr & 0x80 == 0 {
+      return _raise("Bad packet header");
+    }
+
+    let trunc = || _raise("Truncated packet header");
+
+    let (tag, hdr_ln, ln) = if hdr & 0x40 == 0 {
+      // old format
+      let tag = (hdr >> 2) & 0x0F;
+      let n = match hdr & 3 {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        _ => return _raise("Indeterminate packet length is not supported"),
+      };
+      match data.get(1..=n) {
+        Some(l) => (tag, 1 + n, be(l)),
+        None => return trunc(),
+      }
+    } else {
+      // new format
+      let tag = hdr & 0x3F;
+      match data.get(1..) {
+        Some([o, ..]) if *o < 192 => (tag, 2, *o as usize),
+        Some([o1, o2, ..]) if *o1 < 224 => {
+          (tag, 3, ((*o1 as usize - 192) << 8) + *o2 as usize + 192)
+        }
+        Some([255, l @ ..]) if l.len() >= 4 => (tag, 6, be(&l[..4])),
+        Some([o, ..]) if *o >= 224 => return _raise("Partial packet length is not supported"),
+        _ => return trunc(),
+      }
+    };
+
+    match data.get(hdr_ln..hdr_ln + ln) {
+      Some(body) => v.push((tag, body)),
+      None => return _raise("Truncated packet"),
+    }
+    data = &data[hdr_ln + ln..];
+  }
+
+  Ok(v)
+}
+
+/// Split signature subpackets into `(type, data)`, critical bit is dropped
+fn subpackets(mut data: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+  let mut v = vec![];
+
+  while !data.is_empty() {
+    let (hdr_ln, ln) = match data {
+      [o, ..] if *o < 192 => (1, *o as usize),
+      [o1, o2, ..] if *o1 < 255 => (2, ((*o1 as usize - 192) << 8) + *o2 as usize + 192),
+      [255, l @ ..] if l.len() >= 4 => (5, be(&l[..4])),
+      _ => return None,
+    };
+    let (&t, body) = data.get(hdr_ln..hdr_ln + ln)?.split_first()?;
+    v.push((t & 0x7F, body));
+    data = &data[hdr_ln + ln..];
+  }
+
+  Some(v)
+}
+
+/// Self-certification of user ID
+struct SelfCert {
+  created: u32,
+  primary: bool,
+}
+
+impl SelfCert {
+  /// Parse signature packet body as certification of user ID issued by `key`
+  ///
+  /// Signature itself is not verified. Returns `None` for other
+  /// signatures and for ones that can't be parsed
+  fn parse(body: &[u8], key: &KeyPacket) -> Option<SelfCert> {
+    let (version, sig_type) = (*body.first()?, *body.get(1)?);
+    if !(0x10..=0x13).contains(&sig_type) {
+      return None;
+    }
+    let n = match version {
+      4 | 5 => 2,
+      6 => 4,
+      _ => return None,
+    };
+
+    // hashed and unhashed subpacket areas, each prefixed with length
+    let area = |at: usize| {
+      let ln = be(body.get(at..at + n)?);
+      Some((body.get(at + n..at + n + ln)?, at + n + ln))
+    };
+    let (hashed, end) = area(4)?;
+    let (unhashed, _) = area(end)?;
+
+    let mut cert = SelfCert {
+      created: 0,
+      primary: false,
+    };
+    let mut issued = false;
+    for (area, is_hashed) in [(hashed, true), (unhashed, false)] {
+      for (t, d) in subpackets(area)? {
+        match t {
+          SUB_ISSUER => issued |= d == key.key_id(),
+          SUB_ISSUER_FINGERPRINT => issued |= d.get(1..) == Some(&key.fingerprint[..]),
+          SUB_CREATED if is_hashed && d.len() == 4 => cert.created = be(d) as u32,
+          SUB_PRIMARY_USER_ID if is_hashed => cert.primary = d.first().copied().unwrap_or(0) != 0,
+          _ => (),
+        }
+      }
+    }
+
+    if issued {
+      Some(cert)
+    } else {
+      None
+    }
+  }
+}
+
+/// Parse binary transferable public keys (e.g. `gpg --export` output)
+///
+/// Primary user ID is the one with the latest self-signature
+/// marked as primary, signatures are not verified
+pub fn parse_binary(data: &[u8]) -> Result<Vec<TransferableKey>> {
+  let mut keys: Vec<TransferableKey> = vec![];
+  // user ID the following signatures belong to and time of the primary one
+  let mut user_id = None;
+  let mut primary_created = 0;
+
+  for (tag, body) in packets(data)? {
+    match tag {
+      TAG_PUBLIC_KEY => {
+        keys.push(TransferableKey {
+          primary: KeyPacket::parse(body, false)?,
+          user_ids: vec![],
+          primary_user_id: None,
+          subkeys: vec![],
+        });
+        user_id = None;
+      }
+      TAG_USER_ID | TAG_PUBLIC_SUBKEY | TAG_SIGNATURE => {
+        let key = match keys.last_mut() {
+          Some(k) => k,
+          None if tag == TAG_SIGNATURE => continue, // detached
+          None => return _raise("Packet outside of public key"),
+        };
+        match tag {
+          TAG_USER_ID => {
+            key
+              .user_ids
+              .push(String::from_utf8_lossy(body).into_owned());
+            user_id = Some(key.user_ids.len() - 1);
+          }
+          TAG_PUBLIC_SUBKEY => {
+            key.subkeys.push(KeyPacket::parse(body, true)?);
+            user_id = None;
+          }
+          _ => match (user_id, SelfCert::parse(body, &key.primary)) {
+            (Some(i), Some(c))
+              if c.primary && (key.primary_user_id.is_none() || c.created >= primary_created) =>
+            {
+              key.primary_user_id = Some(i);
+              primary_created = c.created;
+            }
+            _ => (),
+          },
+        }
+      }
+      TAG_USER_ATTRIBUTE => user_id = None,
+      _ => (), // trust
+    }
+  }
+
+  if keys.is_empty() {
+    return _raise("No public keys found");
+  }
+
+  Ok(keys)
+}
+
+/// Parse ASCII-armored or binary transferable public keys
+pub fn parse(data: &[u8]) -> Result<Vec<TransferableKey>> {
+  let text = std::str::from_utf8(data).ok().map(str::trim_start);
+  match text {
+    Some(t) if t.starts_with(ARMOR_BEGIN) => parse_binary(&dearmor(t)?),
+    _ => parse_binary(data),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // keys are produced by GnuPG 2.2
+
+  const ALICE: &str = "\
+-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatMXyRYJKwYBBAHaRw8BAQdAUTYJ/tqPGIBHTzUq1lXlLkoP9A3lI9xR/7M7
+GNkXojO0GUFsaWNlIDxhbGljZUBleGFtcGxlLmNvbT6IlgQTFggAPhYhBC1xw5jc
+YNBAvemi8bbpiklGi58SBQJq0xfJAhsDBQkDwmcABQsJCAcCBhUKCQgLAgQWAgMB
+Ah4BAheAAAoJELbpiklGi58SAwYBAIDo6tZ+O3+9en+M2p38jMe2oazUJ2yosl7W
+o3fJTN0qAP48w/NGzruDOp8aCMdSoLgtecaSZKWDSzst8cykEjyRC7g4BGrTF8kS
+CisGAQQBl1UBBQEBB0BrMSHyMf0pvQs2vqloVSXzpS0bPuJUFS0qQL/bGr6YdAMB
+CAeIeAQYFggAIBYhBC1xw5jcYNBAvemi8bbpiklGi58SBQJq0xfJAhsMAAoJELbp
+iklGi58SNWwBAP6zBvzub2zGOMQ8ty6ASczn+5kJPYZ2hZZRJhH+p+BoAP0axaVx
+5g2AUWbM4sjKCRW5Xzrr5mW9buFZgT8JgnZ4CQ==
+=f/Cv
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+  const BOB: &str = "\
+-----BEGIN PGP PUBLIC KEY BLOCK-----
+Comment: test key
+
+mQENBGrTF8kBCAC+vQd18yFZV/5QkZHR97dBiCEmOrSCl7R/BVS3tslu3YtUVM9u
+vWPvZo/KTpJA1ZRP96B3hrIpMWKwq/J4uG5wcBf3DBA7Qdv70NlvSEeTaOiTZZ67
+qOMjJCP0aTyDzXP2Ii2FKPCL/wff8M+A9szie1fqWuwQtOndXNrefBZPEdw9Ckpw
+eQdOYEZKV5gnMUv23Pkna+ohI8dVvGNIYK9RU9Gn1E5Rlp3cOMsqJMJNsKDAHWGI
+BuhgHHMxt+vqJlQ7QFd0qtXOEi+5WVFQP2b275GRhyJn7xq5+ipdk5lf/HcGeUmn
+yXEtBz3unpNWZmDrLjxJldk6iiZX5xaRC6IzABEBAAG0B0JvYiBSU0GJAVQEEwEK
+AD4WIQSfSaZuyH1U0Ri2sgGFaZLg1qATjAUCatMXyQIbAwUJA8JnAAULCQgHAgYV
+CgkICwIEFgIDAQIeAQIXgAAKCRCFaZLg1qATjECUB/4ifnBoPPQFf5RtZvX+57Va
+eYRY236vOWXXhh3FBzmOz1oJVJm01Vfsi9kQIjY8fDFimr2wA6RIfzZF64tohzsJ
+6Hqi8fm+S16emv+GsGD3tw/lCY/KpUE1SwhfO0Pk1Qlwq42j6MpdcdUQVXcLp8dm
+snypQxK9vl0/zBAI7Hay5zl5plaIdGwcBmugnSskupIKF+eFaynUBRy76qwvVq9b
+15efVee0ZBVHjJx3ER+sHeMQyrvnxT8BF45axFVQ6/ycu+mOm+nMyb1MYTsLZxbt
+Ap0aPIoDhV9XTx11G4xkSFfImf6OOYokKYdiVR0KvSpTIXVukcPiIIs+LSehAe1U
+=KK9Z
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+  // second user ID is set as primary by `gpg --quick-set-primary-uid`
+  const CAROL: &str = "\
+-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatMlSxYJKwYBBAHaRw8BAQdAIhH9uqI/AurX8qdsF5DhJq962e6UCPv++qOl
+3ZuYvu+0GUNhcm9sIDxjYXJvbEBleGFtcGxlLmNvbT6IkAQTFggAOBYhBL4uNePl
+P9LdvLZ8DCoEK0nWbMvoBQJq0yVLAhsBBQsJCAcCBhUKCQgLAgQWAgMBAh4BAheA
+AAoJECoEK0nWbMvocuEBAOREV6G2ZX7h26FEm/Jbpm6sR2WH9XobfBQL0p+o9jKe
+AQDfgbmvNnANEqk+XbX4G40eX4R298ZJeufckJ+B02MuArQfQ2Fyb2wgV29yayA8
+Y2Fyb2xAd29yay5leGFtcGxlPoiTBBMWCAA7AhsBBQsJCAcCBhUKCQgLAgQWAgMB
+Ah4BAheAFiEEvi414+U/0t28tnwMKgQrSdZsy+gFAmrTJUwCGQEACgkQKgQrSdZs
+y+jKMgEA10AELMJ823UsXnV829Kirs/pkXngLsmNtcqKA4Ji2BwBAKDNFgP0wlgm
+KUuSE+1Oqhig/6XMRTzwFM4I5PsKk3cL
+=yV2f
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+  #[test]
+  fn test_v4_keys() {
+    let keys = parse(ALICE.as_bytes()).unwrap();
+    assert_eq!(keys.len(), 1);
+
+    let k = &keys[0];
+    assert_eq!(k.user_ids, ["Alice <alice@example.com>"]);
+    assert_eq!(k.primary_user_id, None);
+    assert_eq!(k.user_id(), Some("Alice <alice@example.com>"));
+    assert_eq!(
+      k.primary.fingerprint_hex(),
+      "2D71C398DC60D040BDE9A2F1B6E98A49468B9F12"
+    );
+    assert_eq!(hex::encode(k.primary.key_id()), "b6e98a49468b9f12");
+    assert_eq!(k.primary.name, "ed25519");
+    assert_eq!(
+      k.subkeys[0].fingerprint_hex(),
+      "FFF625CF3082A8864E72D1FCE862B4B334CC1F8A"
+    );
+    assert_eq!(k.subkeys[0].name, "cv25519");
+
+    let arts = k.arts();
+    assert_eq!(arts.len(), 2);
+    assert_eq!(
+      arts[0].to_string(),
+      "pub ed25519 2D71C398DC60D040BDE9A2F1B6E98A49468B9F12 Alice <alice@example.com>"
+    );
+    assert!(arts[0].draw().starts_with("+[Alice <alice@ex]+\n"));
+    assert!(arts[1]
+      .draw()
+      .ends_with("|  Eo+=+    .... o|\n+--[sub cv25519]--+\n"));
+  }
+
+  #[test]
+  fn test_primary_user_id() {
+    let bin = dearmor(CAROL).unwrap();
+    let k = parse_binary(&bin).unwrap().remove(0);
+    assert_eq!(
+      k.user_ids,
+      [
+        "Carol <carol@example.com>",
+        "Carol Work <carol@work.example>"
+      ]
+    );
+    assert_eq!(k.primary_user_id, Some(1));
+    assert_eq!(k.arts()[0].user_id, "Carol Work <carol@work.example>");
+
+    // same user IDs and signatures on another key are not self-signatures
+    let alice = dearmor(ALICE).unwrap();
+    let key_ln = |b: &[u8]| packets(b).unwrap()[0].1.len() + 2;
+    let forged = [&alice[..key_ln(&alice)], &bin[key_ln(&bin)..]].concat();
+    let k = parse_binary(&forged).unwrap().remove(0);
+    assert_eq!(k.primary_user_id, None);
+    assert_eq!(k.user_id(), Some("Carol <carol@example.com>"));
+  }
+
+  #[test]
+  fn test_binary_rsa() {
+    let bin = dearmor(BOB).unwrap();
+    let keys = parse(&bin).unwrap();
+
+    assert_eq!(keys[0].primary.name, "rsa2048");
+    assert_eq!(
+      keys[0].primary.fingerprint_hex(),
+      "9F49A66EC87D54D118B6B201856992E0D6A0138C"
+    );
+
+    // both blocks in one file
+    let both = parse(format!("{}{}", ALICE, BOB).as_bytes()).unwrap();
+    assert_eq!(both.len(), 2);
+  }
+
+  #[test]
+  fn test_v5_v6_fingerprints() {
+    let key = |v: u8| {
+      let mut body = vec![v, 0, 0, 0, 1, 27, 0, 0, 0, 32];
+      body.extend_from_slice(&[0x11; 32]);
+      let mut pkt = vec![0xC0 | TAG_PUBLIC_KEY, body.len() as u8];
+      pkt.extend_from_slice(&body);
+      parse_binary(&pkt).unwrap().remove(0).primary
+    };
+
+    let v6 = key(6);
+    assert_eq!(
+      v6.fingerprint_hex(),
+      "BF29049C691C826DD3088EA0795A3989D5ADC2F89CCE1881F13112DC8C0049D6"
+    );
+    assert_eq!(hex::encode(v6.key_id()), "bf29049c691c826d");
+    assert_eq!(v6.name, "ed25519");
+
+    assert_eq!(
+      key(5).fingerprint_hex(),
+      "C339AE3F6A83D504DC63680D604A454297E82F40092D93CF2CD744EF21192780"
+    );
+  }
+
+  #[test]
+  fn test_bad_input() {
+    let bad_crc = ALICE.replace("=f/Cv", "=AAAA");
+    assert!(parse(bad_crc.as_bytes()).is_err());
+    assert!(parse(b"-----BEGIN PGP PUBLIC KEY BLOCK-----\n\nmDME").is_err());
+    assert!(parse(&[0xC0 | TAG_USER_ID, 1, b'x']).is_err());
+    assert!(parse(b"").is_err());
+  }
+}
@@ -0,0 +1,545 @@
+use crate::bishop_art::{BishopResult, DrawingOptions, Layout};
+use crate::color::{Color, ColorDepth, ColorOptions, Part};
+use crate::errors::{Error, Result};
+
+use std::io::{self, Write};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+mod font;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Maximum length of stored deflate block
+const DEFLATE_BLOCK: usize = 65535;
+
+/// Largest image drawn by [`draw()`], in pixels
+///
+/// [`draw()`]: ./fn.draw.html
+pub const MAX_PIXELS: u64 = 1 << 28;
+
+/// How field is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RasterMode {
+  /// Text output with frame and labels, drawn with embedded 5x7 bitmap font
+  Glyphs,
+  /// Filled squares colored by visit count
+  Squares,
+}
+
+/// Options for raster drawing
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+  pub mode: RasterMode,
+
+  /// Square size in squares mode, in pixels
+  ///
+  /// In glyph mode font is scaled by `cell_size / 9` (at least 1),
+  /// so each char takes 6x9 pixels at scale 1
+  pub cell_size: u32,
+
+  /// Width of frame around the field in pixels (squares mode), `0` to disable frame
+  ///
+  /// Frame is not drawn either if [`FrameStyle`] has no frame chars
+  ///
+  /// [`FrameStyle`]: ../bishop_art/struct.FrameStyle.html
+  pub frame_width: u32,
+
+  pub background: Color,
+
+  /// Color of glyphs and frame if [`color`] has no color for them
+  ///
+  /// [`color`]: ../bishop_art/struct.DrawingOptions.html#structfield.color
+  pub foreground: Color,
+}
+
+impl Default for RasterOptions {
+  fn default() -> Self {
+    RasterOptions {
+      mode: RasterMode::Glyphs,
+      cell_size: 18,
+      frame_width: 2,
+      background: Color::Rgb(255, 255, 255),
+      foreground: Color::Rgb(0, 0, 0),
+    }
+  }
+}
+
+/// RGB image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+  pub width: u32,
+  pub height: u32,
+  /// Pixels row by row, 3 bytes per pixel
+  pub pixels: Vec<u8>,
+}
+
+impl Image {
+  /// Create image filled with color
+  pub fn new(width: u32, height: u32, fill: Color) -> Self {
+    let (r, g, b) = fill.rgb();
+    let n = width as usize * height as usize;
+    Image {
+      width,
+      height,
+      pixels: [r, g, b].repeat(n),
+    }
+  }
+
+  /// Fill rectangle with color, parts outside of image are ignored
+  pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, c: Color) {
+    let (r, g, b) = c.rgb();
+    for py in y..(y + h).min(self.height) {
+      for px in x..(x + w).min(self.width) {
+        let i = (py as usize * self.width as usize + px as usize) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+      }
+    }
+  }
+
+  /// Draw glyph of char with top left corner at `(x, y)`
+  fn draw_char(&mut self, x: u32, y: u32, c: char, scale: u32, color: Color) {
+    let rows = font::glyph(c);
+    for (gy, row) in (0..font::GLYPH_HEIGHT).zip(rows.iter()) {
+      for gx in 0..font::GLYPH_WIDTH {
+        if (row >> (font::GLYPH_WIDTH - 1 - gx)) & 1 != 0 {
+          self.fill_rect(x + gx * scale, y + gy * scale, scale, scale, color);
+        }
+      }
+    }
+  }
+
+  /// Draw box-drawing char as lines reaching the edges of its cell,
+  /// so frame lines join; returns `false` for other chars
+  fn draw_box(&mut self, x: u32, y: u32, c: char, scale: u32, color: Color) -> bool {
+    let ([l, r, u, d], weight) = match font::box_arms(c) {
+      Some(b) => b,
+      None => return false,
+    };
+    let (w, h) = (font::CELL_WIDTH * scale, font::CELL_HEIGHT * scale);
+    let t = weight * scale;
+    let (bx, by) = (x + (w - t) / 2, y + (h - t) / 2);
+
+    if l {
+      self.fill_rect(x, by, bx + t - x, t, color);
+    }
+    if r {
+      self.fill_rect(bx, by, x + w - bx, t, color);
+    }
+    if u {
+      self.fill_rect(bx, y, t, by + t - y, color);
+    }
+    if d {
+      self.fill_rect(bx, by, t, y + h - by, color);
+    }
+    true
+  }
+
+  /// Get pixel color
+  pub fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+    let i = (y as usize * self.width as usize + x as usize) * 3;
+    (self.pixels[i], self.pixels[i + 1], self.pixels[i + 2])
+  }
+
+  /// Write binary PPM (`P6`)
+  pub fn write_ppm<W: Write>(&self, mut w: W) -> io::Result<()> {
+    write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+    w.write_all(&self.pixels)
+  }
+
+  /// Write binary PGM (`P5`), colors are converted to luma (BT.601)
+  pub fn write_pgm<W: Write>(&self, mut w: W) -> io::Result<()> {
+    write!(w, "P5\n{} {}\n255\n", self.width, self.height)?;
+    let gray: Vec<u8> = self
+      .pixels
+      .chunks(3)
+      .map(|p| {
+        let l = 299 * u32::from(p[0]) + 587 * u32::from(p[1]) + 114 * u32::from(p[2]);
+        ((l + 500) / 1000) as u8
+      })
+      .collect();
+    w.write_all(&gray)
+  }
+
+  /// Write 8-bit RGB PNG
+  ///
+  /// Image data is stored in uncompressed deflate blocks
+  pub fn write_png<W: Write>(&self, mut w: W) -> io::Result<()> {
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&self.width.to_be_bytes());
+    ihdr.extend_from_slice(&self.height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // depth, RGB, deflate, no filter, no interlace
+
+    let row = self.width as usize * 3;
+    let mut raw = Vec::with_capacity((row + 1) * self.height as usize);
+    for r in self.pixels.chunks(row.max(1)) {
+      raw.push(0); // filter type: none
+      raw.extend_from_slice(r);
+    }
+
+    w.write_all(PNG_SIGNATURE)?;
+    png_chunk(&mut w, b"IHDR", &ihdr)?;
+    png_chunk(&mut w, b"IDAT", &zlib_stored(&raw))?;
+    png_chunk(&mut w, b"IEND", &[])
+  }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+  let mut crc = !0u32;
+  for &b in data {
+    crc ^= u32::from(b);
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ 0xEDB8_8320
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+  let (mut a, mut b) = (1u32, 0u32);
+  for &x in data {
+    a = (a + u32::from(x)) % 65521;
+    b = (b + a) % 65521;
+  }
+  b << 16 | a
+}
+
+/// zlib stream with data in stored (uncompressed) deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+  let mut out = Vec::with_capacity(data.len() + data.len() / DEFLATE_BLOCK * 5 + 11);
+  out.extend_from_slice(&[0x78, 0x01]);
+
+  let mut blocks = data.chunks(DEFLATE_BLOCK).peekable();
+  if blocks.peek().is_none() {
+    out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+  }
+  while let Some(b) = blocks.next() {
+    let last = blocks.peek().is_none();
+    let ln = b.len() as u16;
+    out.push(last as u8);
+    out.extend_from_slice(&ln.to_le_bytes());
+    out.extend_from_slice(&(!ln).to_le_bytes());
+    out.extend_from_slice(b);
+  }
+
+  out.extend_from_slice(&adler32(data).to_be_bytes());
+  out
+}
+
+fn png_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+  w.write_all(&(data.len() as u32).to_be_bytes())?;
+  w.write_all(kind)?;
+  w.write_all(data)?;
+
+  let mut crc_data = Vec::with_capacity(data.len() + 4);
+  crc_data.extend_from_slice(kind);
+  crc_data.extend_from_slice(data);
+  w.write_all(&crc32(&crc_data).to_be_bytes())
+}
+
+/// Image size if both sides fit in `u32` and area is within [`MAX_PIXELS`]
+///
+/// [`MAX_PIXELS`]: ./constant.MAX_PIXELS.html
+fn image_size(w: Option<u32>, h: Option<u32>) -> Result<(u32, u32)> {
+  match (w, h) {
+    (Some(w), Some(h)) if u64::from(w) * u64::from(h) <= MAX_PIXELS => Ok((w, h)),
+    _ => Err(Error::BadImage(format!(
+      "larger than {} pixels",
+      MAX_PIXELS
+    ))),
+  }
+}
+
+/// Font scale and char cell size in glyph mode
+fn glyph_cell(r: &RasterOptions) -> (u32, u32, u32) {
+  let scale = (r.cell_size / font::CELL_HEIGHT).max(1);
+  (scale, font::CELL_WIDTH * scale, font::CELL_HEIGHT * scale)
+}
+
+/// Text of each line and size of glyph mode image
+fn glyph_texts(
+  lines: &[Vec<(Part, char)>],
+  r: &RasterOptions,
+) -> Result<(Vec<String>, (u32, u32))> {
+  let (_, cw, ch) = glyph_cell(r);
+  let texts: Vec<String> = lines
+    .iter()
+    .map(|l| l.iter().map(|&(_, c)| c).collect())
+    .collect();
+  let cols = texts.iter().map(|t| t.width()).max().unwrap_or(0) as u32;
+  let size = image_size(cols.checked_mul(cw), (lines.len() as u32).checked_mul(ch))?;
+  Ok((texts, size))
+}
+
+/// Size of squares mode image and frame width
+fn squares_size(
+  res: &BishopResult,
+  o: &DrawingOptions,
+  r: &RasterOptions,
+) -> Result<(u32, u32, u32)> {
+  let (w, h) = (res.width() as u32, res.height() as u32);
+  let fw = if o.frame.chars.is_some() {
+    r.frame_width
+  } else {
+    0
+  };
+  let cs = r.cell_size;
+  let shift = if res.layout() == Layout::Hex {
+    cs / 2
+  } else {
+    0
+  };
+  let frame = |v: Option<u32>| v?.checked_add(fw.checked_mul(2)?);
+  let (iw, ih) = (
+    w.checked_mul(cs).and_then(|v| v.checked_add(shift)),
+    h.checked_mul(cs),
+  );
+  let (w, h) = image_size(frame(iw), frame(ih))?;
+  Ok((w, h, fw))
+}
+
+/// Text output rasterized char by char
+fn draw_glyphs(res: &BishopResult, o: &DrawingOptions, r: &RasterOptions) -> Result<Image> {
+  let (scale, cw, ch) = glyph_cell(r);
+  let lines = res.lines(o);
+  let (texts, (w, h)) = glyph_texts(&lines, r)?;
+
+  let mut img = Image::new(w, h, r.background);
+  let color = |p: Part| {
+    o.color
+      .as_ref()
+      .and_then(|c| c.style(p))
+      .and_then(|st| st.color)
+      .unwrap_or(r.foreground)
+  };
+
+  // each grapheme cluster is drawn as its first char,
+  // taking as many cells as columns in text output
+  for (y, (line, text)) in lines.iter().zip(&texts).enumerate() {
+    let (mut i, mut x) = (0, 0);
+    for g in text.graphemes(true) {
+      let (part, c) = line[i];
+      i += g.chars().count();
+      let (px, py) = (x * cw, y as u32 * ch);
+      match g.width() as u32 {
+        0 => continue,
+        gw => x += gw,
+      }
+      if !img.draw_box(px, py, c, scale, color(part)) {
+        // one pixel row of spacing above the glyph
+        img.draw_char(px, py + scale, c, scale, color(part));
+      }
+    }
+  }
+
+  Ok(img)
+}
+
+/// Field as colored squares inside frame,
+/// odd rows are shifted by half a square in hex layout
+fn draw_squares(res: &BishopResult, o: &DrawingOptions, r: &RasterOptions) -> Result<Image> {
+  let default_colors;
+  let colors = match &o.color {
+    Some(c) => c,
+    None => {
+      default_colors = ColorOptions::with_depth(ColorDepth::TrueColor);
+      &default_colors
+    }
+  };
+
+  let (iw, ih, fw) = squares_size(res, o, r)?;
+  let mut img = Image::new(iw, ih, r.background);
+  let (w, h) = (res.width() as u32, res.height() as u32);
+  let cs = r.cell_size;
+  let hex = res.layout() == Layout::Hex;
+  let shift = |y: u32| if hex && y % 2 == 1 { cs / 2 } else { 0 };
+
+  if fw > 0 {
+    let fc = colors.frame.color.unwrap_or(r.foreground);
+    img.fill_rect(0, 0, img.width, img.height, fc);
+    img.fill_rect(fw, fw, iw - fw * 2, ih - fw * 2, r.background);
+  }
+
+  for y in 0..h {
+    for x in 0..w {
+      let part = BishopResult::part(*res.field().get(x as usize, y as usize));
+      if let Part::Background = part {
+        continue;
+      }
+      if let Some(c) = colors.style(part).and_then(|st| st.color) {
+        img.fill_rect(fw + x * cs + shift(y), fw + y * cs, cs, cs, c);
+      }
+    }
+  }
+
+  Ok(img)
+}
+
+/// Size of image drawn by [`draw()`], without drawing it
+///
+/// # Errors
+///
+/// Same as [`draw()`]
+///
+/// [`draw()`]: ./fn.draw.html
+pub fn size(res: &BishopResult, o: &DrawingOptions, r: &RasterOptions) -> Result<(u32, u32)> {
+  match r.mode {
+    RasterMode::Glyphs => glyph_texts(&res.lines(o), r).map(|(_, size)| size),
+    RasterMode::Squares => squares_size(res, o, r).map(|(w, h, _)| (w, h)),
+  }
+}
+
+/// Draw field as RGB image
+///
+/// Char list, frame texts and [`color`] are taken from [`DrawingOptions`],
+/// squares mode falls back to 24-bit palette if colors are not set
+/// and leaves background cells blank
+///
+/// # Errors
+///
+/// [`BadImage`] if image would be larger than [`MAX_PIXELS`]
+///
+/// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+/// [`color`]: ../bishop_art/struct.DrawingOptions.html#structfield.color
+/// [`BadImage`]: ../errors/enum.Error.html#variant.BadImage
+/// [`MAX_PIXELS`]: ./constant.MAX_PIXELS.html
+pub fn draw(res: &BishopResult, o: &DrawingOptions, r: &RasterOptions) -> Result<Image> {
+  match r.mode {
+    RasterMode::Glyphs => draw_glyphs(res, o, r),
+    RasterMode::Squares => draw_squares(res, o, r),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bishop_art::{BishopArt, FrameStyle};
+  use crate::color::Style;
+
+  #[test]
+  fn test_checksums() {
+    assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+
+    let z = zlib_stored(&[7u8; 70000]);
+    assert_eq!(&z[..7], &[0x78, 0x01, 0, 0xFF, 0xFF, 0, 0]);
+    assert_eq!(z[2 + 5 + 65535], 1); // final block
+    assert_eq!(z.len(), 2 + 5 * 2 + 70000 + 4);
+  }
+
+  #[test]
+  fn test_draw() {
+    let res = BishopArt::with_size(5, 5).unwrap().result();
+    let colors = ColorOptions {
+      palette: vec![],
+      start: Style::default(),
+      end: Style::fg(Color::Rgb(255, 0, 0)),
+      frame: Style::fg(Color::Rgb(0, 0, 255)),
+    };
+    let o = DrawingOptions {
+      color: Some(colors),
+      ..Default::default()
+    };
+    let r = RasterOptions {
+      mode: RasterMode::Squares,
+      cell_size: 3,
+      frame_width: 1,
+      ..Default::default()
+    };
+    let img = draw(&res, &o, &r).unwrap();
+
+    assert_eq!((img.width, img.height), (17, 17));
+    assert_eq!(size(&res, &o, &r).unwrap(), (17, 17));
+    assert_eq!(img.pixel(0, 0), (0, 0, 255));
+    assert_eq!(img.pixel(1, 1), (255, 255, 255));
+    // end position in the center overwrites start
+    assert_eq!(img.pixel(7, 7), (255, 0, 0));
+    assert_eq!(img.pixel(9, 9), (255, 0, 0));
+    assert_eq!(img.pixel(10, 10), (255, 255, 255));
+
+    let mut ppm = vec![];
+    img.write_ppm(&mut ppm).unwrap();
+    assert!(ppm.starts_with(b"P6\n17 17\n255\n"));
+    assert_eq!(ppm.len(), 13 + 17 * 17 * 3);
+
+    let mut pgm = vec![];
+    img.write_pgm(&mut pgm).unwrap();
+    assert_eq!(pgm.len(), 13 + 17 * 17);
+    assert_eq!(pgm[13], 29); // blue luma
+
+    let mut png = vec![];
+    img.write_png(&mut png).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+    assert_eq!(&png[16..24], &[0, 0, 0, 17, 0, 0, 0, 17]);
+
+    // sizes overflowing u32 or too large to allocate are errors
+    for cell_size in [300_000_000, 4000] {
+      let huge = RasterOptions {
+        cell_size,
+        ..r.clone()
+      };
+      assert!(matches!(draw(&res, &o, &huge), Err(Error::BadImage(_))));
+    }
+  }
+
+  #[test]
+  fn test_glyphs() {
+    let res = BishopArt::with_size(5, 5).unwrap().result();
+    let o = DrawingOptions {
+      top_text: "\u{2603}".to_string(),
+      ..Default::default()
+    };
+    let r = RasterOptions {
+      cell_size: 20, // scale 2
+      foreground: Color::Rgb(1, 2, 3),
+      ..Default::default()
+    };
+    let img = draw(&res, &o, &r).unwrap();
+    assert_eq!((img.width, img.height), (7 * 12, 7 * 18));
+    assert_eq!(size(&res, &o, &r).unwrap(), (7 * 12, 7 * 18));
+
+    let fg = (1, 2, 3);
+    let bg = (255, 255, 255);
+    let on = |cx: u32, cy: u32, gx: u32, gy: u32| {
+      img.pixel(cx * 12 + gx * 2 + 1, cy * 18 + (gy + 1) * 2 + 1) == fg
+    };
+
+    // "+--[?]--+": corner, dash and replacement box
+    assert!(on(0, 0, 2, 1) && on(0, 0, 0, 3) && !on(0, 0, 0, 1));
+    assert!(on(1, 0, 0, 3) && !on(1, 0, 0, 2));
+    assert!((0..5).all(|g| on(3, 0, g, 0) && on(3, 0, g, 6)));
+    assert!((1..6).all(|g| on(3, 0, 0, g) && !on(3, 0, 2, g)));
+    // "E" in the center
+    assert!((0..5).all(|g| on(3, 3, g, 0) && on(3, 3, g, 6)));
+    assert!(on(3, 3, 0, 3) && !on(3, 3, 4, 3));
+    // spacing between chars and rows
+    assert!((0..img.height).all(|y| img.pixel(11, y) == bg));
+    assert!((0..img.width).all(|x| img.pixel(x, 0) == bg));
+
+    // box-drawing frame lines join across cells
+    let o = DrawingOptions {
+      frame: FrameStyle::single(),
+      ..o
+    };
+    let img = draw(&res, &o, &r).unwrap();
+    let bottom = img.height - 10;
+    assert!((5..img.width - 5).all(|x| img.pixel(x, bottom) == fg));
+    assert!((8..bottom).all(|y| img.pixel(5, y) == fg));
+    assert_eq!((img.pixel(4, 8), img.pixel(5, 7)), (bg, bg));
+
+    // wide and combined clusters take as many cells as in text,
+    // so the right corner stays in the last column
+    let o = DrawingOptions {
+      top_text: "漢e\u{301}".to_string(),
+      ..o
+    };
+    let img = draw(&res, &o, &r).unwrap();
+    assert_eq!(img.width, 7 * 12);
+    assert!((0..12).any(|x| img.pixel(6 * 12 + x, 9) == fg));
+  }
+}
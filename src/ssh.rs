@@ -0,0 +1,641 @@
+use crate::bishop_art::{BishopArt, BishopResult, DrawingOptions};
+use crate::errors::{Error, Result};
+use crate::openssh::{self, KeyLabels};
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine as _;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use std::fmt;
+use std::io::BufRead;
+
+/// Parsing OpenSSH certificates
+pub mod cert;
+
+/// Client for ssh-agent protocol
+#[cfg(all(feature = "agent", unix))]
+pub mod agent;
+
+/// Parsing known_hosts files
+pub mod known_hosts;
+
+fn _raise<T, S: Into<String>>(m: S) -> Result<T> {
+  Err(Error::SshKey(m.into()))
+}
+
+/// Reader for SSH wire format (RFC 4251)
+pub(crate) struct Reader<'a> {
+  buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  pub fn new(buf: &'a [u8]) -> Self {
+    Reader { buf }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.buf.is_empty()
+  }
+
+  pub fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+    if self.buf.len() < n {
+      return _raise("Unexpected end of key data");
+    }
+    let (b, rest) = self.buf.split_at(n);
+    self.buf = rest;
+    Ok(b)
+  }
+
+  pub fn u32(&mut self) -> Result<u32> {
+    let b = self.bytes(4)?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+  }
+
+  pub fn u64(&mut self) -> Result<u64> {
+    let b = self.bytes(8)?;
+    let mut a = [0u8; 8];
+    a.copy_from_slice(b);
+    Ok(u64::from_be_bytes(a))
+  }
+
+  /// Remaining unread data
+  pub fn rest(&self) -> &'a [u8] {
+    self.buf
+  }
+
+  pub fn string(&mut self) -> Result<&'a [u8]> {
+    let ln = self.u32()? as usize;
+    self.bytes(ln)
+  }
+
+  pub fn str(&mut self) -> Result<&'a str> {
+    match std::str::from_utf8(self.string()?) {
+      Ok(s) => Ok(s),
+      Err(_) => _raise("Invalid string in key data"),
+    }
+  }
+
+  /// Read mpint and return its size in bits
+  pub fn mpint_bits(&mut self) -> Result<u32> {
+    let m = self.string()?;
+    let m = match m.iter().position(|&b| b != 0) {
+      Some(i) => &m[i..],
+      None => return Ok(0),
+    };
+    Ok((m.len() as u32 * 8) - m[0].leading_zeros())
+  }
+
+  pub fn finish(&self) -> Result<()> {
+    if self.is_empty() {
+      Ok(())
+    } else {
+      _raise("Trailing data after key")
+    }
+  }
+}
+
+/// Supported public key types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+  Rsa,
+  Dsa,
+  EcdsaP256,
+  EcdsaP384,
+  EcdsaP521,
+  Ed25519,
+  SkEcdsaP256,
+  SkEd25519,
+}
+
+use KeyType::*;
+
+const KEY_TYPES: &[KeyType] = &[
+  Rsa,
+  Dsa,
+  EcdsaP256,
+  EcdsaP384,
+  EcdsaP521,
+  Ed25519,
+  SkEcdsaP256,
+  SkEd25519,
+];
+
+impl KeyType {
+  /// Find key type by its name as used in key files and wire format
+  pub fn from_name(name: &str) -> Option<KeyType> {
+    KEY_TYPES.iter().find(|t| t.name() == name).copied()
+  }
+
+  /// Name used in key files and wire format (e.g. `ssh-ed25519`)
+  pub fn name(self) -> &'static str {
+    match self {
+      Rsa => "ssh-rsa",
+      Dsa => "ssh-dss",
+      EcdsaP256 => "ecdsa-sha2-nistp256",
+      EcdsaP384 => "ecdsa-sha2-nistp384",
+      EcdsaP521 => "ecdsa-sha2-nistp521",
+      Ed25519 => "ssh-ed25519",
+      SkEcdsaP256 => "sk-ecdsa-sha2-nistp256@openssh.com",
+      SkEd25519 => "sk-ssh-ed25519@openssh.com",
+    }
+  }
+
+  /// Find key type by name of its certificate type
+  pub fn from_cert_name(name: &str) -> Option<KeyType> {
+    KEY_TYPES.iter().find(|t| t.cert_name() == name).copied()
+  }
+
+  /// Certificate type name (e.g. `ssh-ed25519-cert-v01@openssh.com`)
+  pub fn cert_name(self) -> String {
+    let name = self.name();
+    match name.find('@') {
+      Some(i) => format!("{}-cert-v01{}", &name[..i], &name[i..]),
+      None => format!("{}-cert-v01@openssh.com", name),
+    }
+  }
+
+  /// Short name as printed by OpenSSH (e.g. `ED25519`)
+  pub fn short_name(self) -> &'static str {
+    match self {
+      Rsa => "RSA",
+      Dsa => "DSA",
+      EcdsaP256 | EcdsaP384 | EcdsaP521 => "ECDSA",
+      Ed25519 => "ED25519",
+      SkEcdsaP256 => "ECDSA-SK",
+      SkEd25519 => "ED25519-SK",
+    }
+  }
+
+  fn curve(self) -> Option<&'static str> {
+    match self {
+      EcdsaP256 | SkEcdsaP256 => Some("nistp256"),
+      EcdsaP384 => Some("nistp384"),
+      EcdsaP521 => Some("nistp521"),
+      _ => None,
+    }
+  }
+}
+
+/// Hash algorithm used for fingerprints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintHash {
+  Sha256,
+  /// Legacy fingerprint, `ssh-keygen -E md5`
+  Md5,
+}
+
+impl FingerprintHash {
+  /// Algorithm name as printed by OpenSSH
+  pub fn name(self) -> &'static str {
+    match self {
+      FingerprintHash::Sha256 => "SHA256",
+      FingerprintHash::Md5 => "MD5",
+    }
+  }
+}
+
+/// Key fingerprint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+  pub hash: FingerprintHash,
+  pub digest: Vec<u8>,
+}
+
+impl Fingerprint {
+  /// Compute fingerprint of key blob
+  pub fn of_blob(hash: FingerprintHash, blob: &[u8]) -> Fingerprint {
+    let digest = match hash {
+      FingerprintHash::Sha256 => Sha256::digest(blob).to_vec(),
+      FingerprintHash::Md5 => Md5::digest(blob).to_vec(),
+    };
+    Fingerprint { hash, digest }
+  }
+}
+
+impl fmt::Display for Fingerprint {
+  /// Formats fingerprint as OpenSSH does,
+  /// e.g. `SHA256:<base64>` or `MD5:aa:bb:...`
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}:", self.hash.name())?;
+    match self.hash {
+      FingerprintHash::Sha256 => write!(f, "{}", STANDARD_NO_PAD.encode(&self.digest)),
+      FingerprintHash::Md5 => {
+        for (i, b) in self.digest.iter().enumerate() {
+          if i > 0 {
+            f.write_str(":")?;
+          }
+          write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// Parsed public key
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+  pub key_type: KeyType,
+  /// Key size in bits
+  pub bits: u32,
+  /// Raw key blob in wire format
+  pub blob: Vec<u8>,
+  pub comment: String,
+  /// Options prefix from `authorized_keys` line
+  pub options: Option<String>,
+}
+
+/// Art for a public key, labelled with key type, size and fingerprint algorithm
+pub struct KeyArt {
+  pub result: BishopResult,
+  pub labels: KeyLabels,
+  pub fingerprint: Fingerprint,
+  pub comment: String,
+}
+
+impl KeyArt {
+  /// Draw art exactly as `ssh-keygen -lv` does (see [`openssh::draw()`])
+  ///
+  /// [`openssh::draw()`]: ../openssh/fn.draw.html
+  pub fn draw(&self) -> String {
+    openssh::draw(&self.result, &self.labels)
+  }
+
+  /// Draw art using parameters from [`DrawingOptions`]
+  ///
+  /// Empty `top_text` and `bottom_text` are replaced with
+  /// key type/size and fingerprint algorithm respectively
+  ///
+  /// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+  pub fn draw_with_opts(&self, o: &DrawingOptions) -> String {
+    let text = |t: &String, d: String| if t.is_empty() { d } else { t.clone() };
+    let l = &self.labels;
+
+    let opts = DrawingOptions {
+      top_text: text(&o.top_text, format!("{} {}", l.key_type, l.bits)),
+      bottom_text: text(&o.bottom_text, l.hash_alg.clone()),
+      ..o.clone()
+    };
+
+    self.result.draw_with_opts(&opts)
+  }
+}
+
+impl fmt::Display for KeyArt {
+  /// Formats fingerprint line as `ssh-keygen -l` does
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let comment = match self.comment.as_str() {
+      "" => "no comment",
+      c => c,
+    };
+    write!(
+      f,
+      "{} {} {} ({})",
+      self.labels.bits, self.fingerprint, comment, self.labels.key_type
+    )
+  }
+}
+
+impl PublicKey {
+  /// Parse key blob in wire format
+  pub fn from_blob<C: Into<String>>(blob: &[u8], comment: C) -> Result<PublicKey> {
+    let mut r = Reader::new(blob);
+    let name = r.str()?;
+
+    let key_type = match KeyType::from_name(name) {
+      Some(t) => t,
+      None => return _raise(format!("Unsupported key type: {}", name)),
+    };
+
+    let bits = Self::read_key(key_type, &mut r)?;
+    r.finish()?;
+
+    Ok(PublicKey {
+      key_type,
+      bits,
+      blob: blob.to_vec(),
+      comment: comment.into(),
+      options: None,
+    })
+  }
+
+  /// Read key parameters following type name, returns key size
+  pub(crate) fn read_key(key_type: KeyType, r: &mut Reader) -> Result<u32> {
+    let bits = match key_type {
+      Rsa => {
+        r.mpint_bits()?; // e
+        r.mpint_bits()? // n
+      }
+      Dsa => {
+        let p = r.mpint_bits()?;
+        for _ in 0..3 {
+          r.mpint_bits()?; // q, g, y
+        }
+        p
+      }
+      EcdsaP256 | EcdsaP384 | EcdsaP521 | SkEcdsaP256 => {
+        if Some(r.str()?) != key_type.curve() {
+          return _raise("Curve doesn't match key type");
+        }
+        r.string()?; // Q
+        match key_type {
+          EcdsaP384 => 384,
+          EcdsaP521 => 521,
+          _ => 256,
+        }
+      }
+      Ed25519 | SkEd25519 => {
+        if r.string()?.len() != 32 {
+          return _raise("Bad ED25519 public key length");
+        }
+        256
+      }
+    };
+
+    if let SkEcdsaP256 | SkEd25519 = key_type {
+      r.str()?; // application
+    }
+
+    Ok(bits)
+  }
+
+  /// Compute fingerprint of the key
+  pub fn fingerprint(&self, hash: FingerprintHash) -> Fingerprint {
+    Fingerprint::of_blob(hash, &self.blob)
+  }
+
+  /// Visualize key fingerprint on field of default size
+  pub fn art(&self, hash: FingerprintHash) -> KeyArt {
+    self.art_with(hash, BishopArt::new())
+  }
+
+  /// Visualize key fingerprint using provided `BishopArt`
+  /// (e.g. one created with [`BishopArt::with_size()`])
+  ///
+  /// [`BishopArt::with_size()`]: ../bishop_art/struct.BishopArt.html#method.with_size
+  pub fn art_with(&self, hash: FingerprintHash, art: BishopArt) -> KeyArt {
+    let fingerprint = self.fingerprint(hash);
+    KeyArt {
+      result: art.chain(&fingerprint.digest).result(),
+      labels: KeyLabels::new(self.key_type.short_name(), self.bits, hash.name()),
+      fingerprint,
+      comment: self.comment.clone(),
+    }
+  }
+}
+
+/// Line of a key file that couldn't be parsed and was skipped
+#[derive(Debug)]
+pub struct SkippedLine {
+  /// Line number in the file, starting from 1
+  pub line: usize,
+  pub error: Error,
+}
+
+impl fmt::Display for SkippedLine {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}: {}", self.line, self.error)
+  }
+}
+
+/// Returns index where options prefix ends, same as
+/// OpenSSH's `sshkey_advance_past_options`
+fn skip_options(s: &str) -> Result<usize> {
+  let b = s.as_bytes();
+  let mut quoted = false;
+  let mut i = 0;
+
+  while i < b.len() && (quoted || (b[i] != b' ' && b[i] != b'\t')) {
+    if b[i] == b'\\' && b.get(i + 1) == Some(&b'"') {
+      i += 1;
+    } else if b[i] == b'"' {
+      quoted = !quoted;
+    }
+    i += 1;
+  }
+
+  if quoted {
+    _raise("Unterminated quote in key options")
+  } else {
+    Ok(i)
+  }
+}
+
+pub(crate) fn split_token(s: &str) -> (&str, &str) {
+  let s = s.trim_start();
+  match s.find(|c: char| c.is_ascii_whitespace()) {
+    Some(i) => (&s[..i], &s[i..]),
+    None => (s, ""),
+  }
+}
+
+pub(crate) fn put_string(v: &mut Vec<u8>, s: &[u8]) {
+  v.extend_from_slice(&(s.len() as u32).to_be_bytes());
+  v.extend_from_slice(s);
+}
+
+pub(crate) fn decode_base64(b64: &str) -> Result<Vec<u8>> {
+  match STANDARD.decode(b64) {
+    Ok(b) => Ok(b),
+    Err(e) => _raise(format!("Bad base64 key data: {}", e)),
+  }
+}
+
+/// Parse `type base64 [comment]` part of the line
+pub(crate) fn parse_key_text(s: &str) -> Result<PublicKey> {
+  let (name, rest) = split_token(s);
+  let (b64, comment) = split_token(rest);
+
+  let blob = decode_base64(b64)?;
+  let key = PublicKey::from_blob(&blob, comment.trim())?;
+  if key.key_type.name() != name {
+    return _raise(format!(
+      "Key type mismatch: {} vs {}",
+      name,
+      key.key_type.name()
+    ));
+  }
+
+  Ok(key)
+}
+
+/// Parse `.pub` file or `authorized_keys` line
+///
+/// Line has form `[options] type base64 [comment]`
+pub fn parse_line(line: &str) -> Result<PublicKey> {
+  let line = line.trim();
+  let (first, _) = split_token(line);
+
+  if KeyType::from_name(first).is_some() {
+    return parse_key_text(line);
+  }
+
+  let opts_end = skip_options(line)?;
+  let mut key = parse_key_text(&line[opts_end..])?;
+  key.options = Some(line[..opts_end].to_string());
+
+  Ok(key)
+}
+
+/// Parse all keys from `.pub` or `authorized_keys` file
+///
+/// Empty lines and comments (`#`) are skipped. Like sshd, lines that
+/// can't be parsed are skipped too and returned separately,
+/// only read errors fail the whole file
+pub fn parse_keys<R: BufRead>(r: R) -> Result<(Vec<PublicKey>, Vec<SkippedLine>)> {
+  let mut keys = vec![];
+  let mut skipped = vec![];
+
+  for (i, line) in r.lines().enumerate() {
+    let line = line.map_err(Error::IO)?;
+    let l = line.trim();
+    if l.is_empty() || l.starts_with('#') {
+      continue;
+    }
+    match parse_line(l) {
+      Ok(k) => keys.push(k),
+      Err(error) => skipped.push(SkippedLine { line: i + 1, error }),
+    }
+  }
+
+  Ok((keys, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // keys and reference fingerprints are produced by `ssh-keygen` (OpenSSH 9.2)
+
+  const ED25519_PUB: &str = "ssh-ed25519 \
+    AAAAC3NzaC1lZDI1NTE5AAAAIKGsuoPPmDU+2fNnrCD21qPnt3D13rGJab9TZfPzPuAO alice@example.com";
+
+  const RSA_PUB: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQDLi0AoJNwpUDKt4JJakvANcoPjOaZ4vX1\
+    /5L+oonIF6LBeTqc+OSv2hvK4PJ9KCQ8OPLJ7VUCDKa7tzB6iYjwONQH1ShE0T/i0dd5JK7Y8zVfBTNDR3aDCwC9z5oPjS8l\
+    VxseJovcpje3UDX0hcY3WeIUTZyvM21KG+LSlgf8qPzpRLpQ5v4wmw0/p6R0GJMGkASaVrhd+9Yi+LIfaM6VxgExOvyPDnvGm\
+    JYit3RC/qJCKBP3smpODmgrvHJ6GHErFurOFogMUnLlJjoxOo9IHJolfznaQPnX9fMH10xYV7Yzxg29qeOWQP/MXFBRkFWEGHZ\
+    IV1yIJZnUg8cg5gIUf rsa key";
+
+  const ECDSA_PUB: &str = "ecdsa-sha2-nistp384 AAAAE2VjZHNhLXNoYTItbmlzdHAzODQAAAAIbmlzdHAzODQAAABhB\
+    DqEXGaaCsjDfYNQI7P1d0+c2b3H4e2eXbPrxWrr1DndQHg7RGJ2g102/jIk8JC9qGEcAaCBHwynJYnvq5kk2/gLJ/I/Ivxdhud\
+    q3o8WhDahxvuvzBwS5YP2ond03A1PcQ== ";
+
+  #[test]
+  fn test_fingerprints() {
+    let set = [
+      (
+        ED25519_PUB,
+        FingerprintHash::Sha256,
+        "256 SHA256:zFePOJlZopCjR1wruE1Rrfowzd+4hXBEsSpg6qkBl2Y alice@example.com (ED25519)",
+      ),
+      (
+        RSA_PUB,
+        FingerprintHash::Sha256,
+        "2048 SHA256:mHkK6Vq63R588jv9Q79GFH0gR8t+icKjY5V+523unBQ rsa key (RSA)",
+      ),
+      (
+        RSA_PUB,
+        FingerprintHash::Md5,
+        "2048 MD5:8c:d0:14:ed:34:08:29:bd:a0:fd:35:67:4d:50:1f:36 rsa key (RSA)",
+      ),
+      (
+        ECDSA_PUB,
+        FingerprintHash::Sha256,
+        "384 SHA256:AjKW51+9VRxAEJlk0qCfSKrQsEJUv6ABOv+5K0w2HFg no comment (ECDSA)",
+      ),
+    ];
+
+    for (line, hash, fp) in &set {
+      let key = parse_line(line).unwrap();
+      assert_eq!(key.art(*hash).to_string(), *fp);
+    }
+  }
+
+  #[test]
+  fn test_art() {
+    let art = parse_line(ED25519_PUB)
+      .unwrap()
+      .art(FingerprintHash::Sha256);
+
+    let ref_art = "\
++--[ED25519 256]--+
+|      ..o.o.     |
+|     o + o..     |
+|    + O ..+ o    |
+|   + B *.+ O o   |
+|. E o ++S O . .  |
+|.= . .+.o+ o     |
+|. o    + ..o.    |
+| o      . o..    |
+|.         ..     |
++----[SHA256]-----+";
+
+    assert_eq!(art.draw(), ref_art);
+    assert_eq!(
+      art.draw_with_opts(&DrawingOptions::default()),
+      format!("{}\n", ref_art)
+    );
+  }
+
+  #[test]
+  fn test_options() {
+    let line = format!("from=\"10.0.0.1,host \\\"x\\\"\",no-pty {}", ED25519_PUB);
+    let key = parse_line(&line).unwrap();
+
+    assert_eq!(key.key_type, Ed25519);
+    assert_eq!(key.comment, "alice@example.com");
+    assert_eq!(
+      key.options.as_deref(),
+      Some("from=\"10.0.0.1,host \\\"x\\\"\",no-pty")
+    );
+
+    assert!(parse_line("from=\"unterminated ssh-ed25519 AAAA").is_err());
+  }
+
+  #[test]
+  fn test_sk_key() {
+    let mut blob = vec![];
+    put_string(&mut blob, SkEd25519.name().as_bytes());
+    put_string(&mut blob, &[7u8; 32]);
+    put_string(&mut blob, b"ssh:");
+
+    let line = format!("{} {} token", SkEd25519.name(), STANDARD.encode(&blob));
+    let key = parse_line(&line).unwrap();
+
+    assert_eq!(key.key_type, SkEd25519);
+    assert_eq!(key.bits, 256);
+    assert_eq!(
+      key.art(FingerprintHash::Sha256).labels.key_type,
+      "ED25519-SK"
+    );
+  }
+
+  #[test]
+  fn test_bad_keys() {
+    // type mismatch
+    let line = ED25519_PUB.replacen("ssh-ed25519", "ssh-rsa", 1);
+    assert!(parse_line(&line).is_err());
+
+    // truncated blob
+    let mut blob = vec![];
+    put_string(&mut blob, b"ssh-ed25519");
+    put_string(&mut blob, &[0u8; 16]);
+    assert!(PublicKey::from_blob(&blob, "").is_err());
+
+    assert!(parse_line("ssh-ed25519 not_base64!").is_err());
+  }
+
+  #[test]
+  fn test_parse_keys() {
+    let file = format!(
+      "# keys\n\n{}\nssh-ed25519 not_base64!\n{}\n",
+      ED25519_PUB, RSA_PUB
+    );
+    let (keys, skipped) = parse_keys(file.as_bytes()).unwrap();
+    let types: Vec<_> = keys.iter().map(|k| k.key_type).collect();
+    assert_eq!(types, [Ed25519, Rsa]);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].line, 4);
+  }
+}
@@ -0,0 +1,214 @@
+use super::cert::Certificate;
+use super::{FingerprintHash, KeyArt, KeyType, PublicKey, Reader};
+use crate::bishop_art::BishopArt;
+use crate::errors::{Error, Result};
+
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH2_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH2_AGENT_IDENTITIES_ANSWER: u8 = 12;
+
+/// Maximum agent message size, same as in OpenSSH
+const AGENT_MAX_LEN: usize = 256 * 1024;
+
+fn _raise<T, S: Into<String>>(m: S) -> Result<T> {
+  Err(Error::Agent(m.into()))
+}
+
+/// Key loaded into agent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identity {
+  /// Key or certificate blob in wire format
+  pub blob: Vec<u8>,
+  pub comment: String,
+}
+
+impl Identity {
+  /// Parse identity blob into key and, for certificates, short certificate name
+  fn parse(&self) -> Result<(PublicKey, Option<String>)> {
+    let name = Reader::new(&self.blob).str()?;
+    if KeyType::from_cert_name(name).is_some() {
+      let c = Certificate::from_blob(&self.blob, self.comment.as_str())?;
+      let cert_name = c.short_name();
+      Ok((c.key, Some(cert_name)))
+    } else {
+      let key = PublicKey::from_blob(&self.blob, self.comment.as_str())?;
+      Ok((key, None))
+    }
+  }
+
+  /// Parse identity blob; for certificates the certified key is returned
+  pub fn key(&self) -> Result<PublicKey> {
+    Ok(self.parse()?.0)
+  }
+
+  /// Visualize identity fingerprint using provided `BishopArt`
+  pub fn art_with(&self, hash: FingerprintHash, art: BishopArt) -> Result<KeyArt> {
+    let (key, cert_name) = self.parse()?;
+    let mut a = key.art_with(hash, art);
+    if let Some(n) = cert_name {
+      a.labels.key_type = n;
+    }
+    Ok(a)
+  }
+}
+
+/// Client for ssh-agent protocol
+///
+/// See `draft-miller-ssh-agent` for protocol description
+pub struct AgentClient<S> {
+  stream: S,
+}
+
+impl AgentClient<UnixStream> {
+  /// Connect to agent listening on unix socket
+  pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self> {
+    Ok(AgentClient::new(
+      UnixStream::connect(path).map_err(Error::IO)?,
+    ))
+  }
+
+  /// Connect to agent using `$SSH_AUTH_SOCK`
+  pub fn from_env() -> Result<Self> {
+    match env::var_os("SSH_AUTH_SOCK") {
+      Some(p) if !p.is_empty() => Self::connect(p),
+      _ => _raise("SSH_AUTH_SOCK is not set"),
+    }
+  }
+}
+
+impl<S: Read + Write> AgentClient<S> {
+  /// Create client over already connected stream
+  pub fn new(stream: S) -> Self {
+    AgentClient { stream }
+  }
+
+  /// Send request message and read reply
+  fn request(&mut self, msg_type: u8, payload: &[u8]) -> Result<Vec<u8>> {
+    let mut msg = Vec::with_capacity(payload.len() + 5);
+    msg.extend_from_slice(&(payload.len() as u32 + 1).to_be_bytes());
+    msg.push(msg_type);
+    msg.extend_from_slice(payload);
+    self.stream.write_all(&msg).map_err(Error::IO)?;
+
+    let mut ln = [0u8; 4];
+    self.stream.read_exact(&mut ln).map_err(Error::IO)?;
+    let ln = u32::from_be_bytes(ln) as usize;
+    if ln == 0 || ln > AGENT_MAX_LEN {
+      return _raise(format!("Bad reply length: {}", ln));
+    }
+
+    let mut reply = vec![0u8; ln];
+    self.stream.read_exact(&mut reply).map_err(Error::IO)?;
+    Ok(reply)
+  }
+
+  /// Request list of loaded identities (`SSH2_AGENTC_REQUEST_IDENTITIES`)
+  pub fn identities(&mut self) -> Result<Vec<Identity>> {
+    let reply = self.request(SSH2_AGENTC_REQUEST_IDENTITIES, &[])?;
+
+    match reply[0] {
+      SSH2_AGENT_IDENTITIES_ANSWER => (),
+      SSH_AGENT_FAILURE => return _raise("Agent refused to list identities"),
+      t => return _raise(format!("Unexpected reply type: {}", t)),
+    }
+
+    let mut r = Reader::new(&reply[1..]);
+    let n = r.u32()?;
+    let mut ids = Vec::with_capacity(n.min(1024) as usize);
+
+    for _ in 0..n {
+      ids.push(Identity {
+        blob: r.string()?.to_vec(),
+        comment: String::from_utf8_lossy(r.string()?).into_owned(),
+      });
+    }
+    r.finish()?;
+
+    Ok(ids)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ssh::{parse_line, put_string};
+  use std::os::unix::net::UnixListener;
+  use std::thread;
+
+  const ED25519_PUB: &str = "ssh-ed25519 \
+    AAAAC3NzaC1lZDI1NTE5AAAAIKGsuoPPmDU+2fNnrCD21qPnt3D13rGJab9TZfPzPuAO alice@example.com";
+
+  /// Stand-in agent answering single request with `reply`
+  fn fake_agent(name: &str, reply: Vec<u8>) -> (std::path::PathBuf, thread::JoinHandle<Vec<u8>>) {
+    let path = env::temp_dir().join(format!("bishop-agent-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let h = thread::spawn(move || {
+      let (mut s, _) = listener.accept().unwrap();
+      let mut req = [0u8; 5];
+      s.read_exact(&mut req).unwrap();
+      s.write_all(&(reply.len() as u32).to_be_bytes()).unwrap();
+      s.write_all(&reply).unwrap();
+      req.to_vec()
+    });
+
+    (path, h)
+  }
+
+  #[test]
+  fn test_identities() {
+    let key = parse_line(ED25519_PUB).unwrap();
+
+    let mut reply = vec![SSH2_AGENT_IDENTITIES_ANSWER];
+    reply.extend_from_slice(&2u32.to_be_bytes());
+    put_string(&mut reply, &key.blob);
+    put_string(&mut reply, b"first");
+    put_string(&mut reply, &key.blob);
+    put_string(&mut reply, b"second");
+
+    let (path, agent) = fake_agent("ids", reply);
+    let ids = AgentClient::connect(&path).unwrap().identities().unwrap();
+    let req = agent.join().unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(req, [0, 0, 0, 1, SSH2_AGENTC_REQUEST_IDENTITIES]);
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[1].comment, "second");
+
+    let art = ids[0]
+      .art_with(FingerprintHash::Sha256, BishopArt::new())
+      .unwrap();
+    assert_eq!(
+      art.result.draw(),
+      key.art(FingerprintHash::Sha256).result.draw()
+    );
+
+    let garbled = Identity {
+      blob: b"\0\0\0\x07ssh-foo".to_vec(),
+      comment: "garbled".into(),
+    };
+    match garbled.art_with(FingerprintHash::Sha256, BishopArt::new()) {
+      Err(Error::SshKey(_)) => (),
+      _ => panic!("unsupported key type should be reported"),
+    }
+  }
+
+  #[test]
+  fn test_failure() {
+    let (path, agent) = fake_agent("fail", vec![SSH_AGENT_FAILURE]);
+    let res = AgentClient::connect(&path).unwrap().identities();
+    agent.join().unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    match res {
+      Err(Error::Agent(_)) => (),
+      _ => panic!("agent failure should be reported"),
+    }
+  }
+}
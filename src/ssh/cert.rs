@@ -0,0 +1,412 @@
+use super::{
+  decode_base64, put_string, split_token, FingerprintHash, KeyArt, KeyType, PublicKey, Reader,
+};
+use crate::bishop_art::{side_by_side, BishopArt, DrawingOptions};
+use crate::errors::Result;
+
+use std::fmt::Write as _;
+
+/// Certificate type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertType {
+  User,
+  Host,
+}
+
+/// Parsed OpenSSH certificate (`*-cert-v01@openssh.com`)
+///
+/// See `PROTOCOL.certkeys` in OpenSSH sources for format description
+#[derive(Debug, Clone, PartialEq)]
+pub struct Certificate {
+  /// Certified public key
+  pub key: PublicKey,
+  pub serial: u64,
+  pub cert_type: CertType,
+  pub key_id: String,
+  pub principals: Vec<String>,
+  /// Seconds since unix epoch
+  pub valid_after: u64,
+  /// Seconds since unix epoch, `u64::MAX` means forever
+  pub valid_before: u64,
+  /// Critical options as `name value` (e.g. `force-command /bin/true`)
+  pub critical_options: Vec<String>,
+  /// Extensions, usually just names (e.g. `permit-pty`)
+  pub extensions: Vec<String>,
+  /// Signing CA key
+  pub ca: PublicKey,
+  /// Signature algorithm used by CA (e.g. `rsa-sha2-512`)
+  pub signature_type: String,
+  /// Raw certificate blob in wire format
+  pub blob: Vec<u8>,
+  pub comment: String,
+}
+
+/// Check if `.pub` line (`type base64 [comment]`) contains a certificate
+pub fn is_cert(line: &str) -> bool {
+  KeyType::from_cert_name(split_token(line).0).is_some()
+}
+
+fn read_strings(buf: &[u8]) -> Result<Vec<String>> {
+  let mut r = Reader::new(buf);
+  let mut v = vec![];
+  while !r.is_empty() {
+    v.push(r.str()?.to_string());
+  }
+  Ok(v)
+}
+
+/// Decode option data holding single string (e.g. `force-command`)
+fn option_value(data: &[u8]) -> Option<&str> {
+  let mut r = Reader::new(data);
+  let v = r.str().ok()?;
+  r.finish().ok().map(|_| v)
+}
+
+/// Read name/data pairs of options or extensions as `name [value]` like
+/// `ssh-keygen -L`, data not holding single string is shown as its length
+fn read_options(buf: &[u8]) -> Result<Vec<String>> {
+  let mut r = Reader::new(buf);
+  let mut v = vec![];
+  while !r.is_empty() {
+    let name = r.str()?;
+    let data = r.string()?;
+    v.push(match option_value(data) {
+      _ if data.is_empty() => name.to_string(),
+      Some(value) => format!("{} {}", name, value),
+      None => format!("{} (len {})", name, data.len()),
+    });
+  }
+  Ok(v)
+}
+
+/// Format unix time as `YYYY-MM-DDTHH:MM:SS UTC`
+fn format_time(t: u64) -> String {
+  let (days, secs) = ((t / 86400) as i64, t % 86400);
+
+  // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z - era * 146_097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let d = doy - (153 * mp + 2) / 5 + 1;
+  let m = if mp < 10 { mp + 3 } else { mp - 9 };
+  let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+  format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02} UTC",
+    y,
+    m,
+    d,
+    secs / 3600,
+    secs / 60 % 60,
+    secs % 60
+  )
+}
+
+impl Certificate {
+  /// Parse certificate blob in wire format
+  pub fn from_blob<C: Into<String>>(blob: &[u8], comment: C) -> Result<Certificate> {
+    let mut r = Reader::new(blob);
+    let name = r.str()?;
+
+    let key_type = match KeyType::from_cert_name(name) {
+      Some(t) => t,
+      None => return super::_raise(format!("Unsupported certificate type: {}", name)),
+    };
+
+    r.string()?; // nonce
+
+    // certified key fields are the same as in plain key blob
+    let fields = r.rest();
+    let bits = PublicKey::read_key(key_type, &mut r)?;
+    let fields = &fields[..fields.len() - r.rest().len()];
+
+    let mut key_blob = vec![];
+    put_string(&mut key_blob, key_type.name().as_bytes());
+    key_blob.extend_from_slice(fields);
+
+    let comment = comment.into();
+    let key = PublicKey {
+      key_type,
+      bits,
+      blob: key_blob,
+      comment: comment.clone(),
+      options: None,
+    };
+
+    let serial = r.u64()?;
+    let cert_type = match r.u32()? {
+      1 => CertType::User,
+      2 => CertType::Host,
+      t => return super::_raise(format!("Unknown certificate type: {}", t)),
+    };
+    let key_id = r.str()?.to_string();
+    let principals = read_strings(r.string()?)?;
+    let valid_after = r.u64()?;
+    let valid_before = r.u64()?;
+    let critical_options = read_options(r.string()?)?;
+    let extensions = read_options(r.string()?)?;
+    r.string()?; // reserved
+    let ca = PublicKey::from_blob(r.string()?, "")?;
+    let signature_type = Reader::new(r.string()?).str()?.to_string();
+    r.finish()?;
+
+    Ok(Certificate {
+      key,
+      serial,
+      cert_type,
+      key_id,
+      principals,
+      valid_after,
+      valid_before,
+      critical_options,
+      extensions,
+      ca,
+      signature_type,
+      blob: blob.to_vec(),
+      comment,
+    })
+  }
+
+  /// Short certificate type name as printed by OpenSSH (e.g. `ED25519-CERT`)
+  pub fn short_name(&self) -> String {
+    format!("{}-CERT", self.key.key_type.short_name())
+  }
+
+  /// Visualize fingerprints of certified key and CA key on fields of default size
+  pub fn arts(&self, hash: FingerprintHash) -> (KeyArt, KeyArt) {
+    self.arts_with(hash, BishopArt::new(), BishopArt::new())
+  }
+
+  /// Visualize fingerprints of certified key and CA key using provided `BishopArt`s
+  pub fn arts_with(
+    &self,
+    hash: FingerprintHash,
+    key_art: BishopArt,
+    ca_art: BishopArt,
+  ) -> (KeyArt, KeyArt) {
+    let mut key = self.key.art_with(hash, key_art);
+    key.labels.key_type = self.short_name();
+    (key, self.ca.art_with(hash, ca_art))
+  }
+
+  /// Draw certified key and CA arts side by side
+  /// using parameters from [`DrawingOptions`]
+  ///
+  /// Top borders are labelled `key` and `CA`,
+  /// `top_text` from options is ignored
+  ///
+  /// [`DrawingOptions`]: ../../bishop_art/struct.DrawingOptions.html
+  pub fn draw_arts(arts: &(KeyArt, KeyArt), o: &DrawingOptions) -> String {
+    let draw = |a: &KeyArt, label: &str| {
+      a.draw_with_opts(&DrawingOptions {
+        top_text: label.to_string(),
+        ..o.clone()
+      })
+    };
+
+    side_by_side(&[draw(&arts.0, "key"), draw(&arts.1, "CA")], 2)
+  }
+
+  /// Draw certified key and CA arts side by side using default parameters
+  pub fn draw(&self, hash: FingerprintHash) -> String {
+    Self::draw_arts(&self.arts(hash), &DrawingOptions::default())
+  }
+
+  /// Describe certificate the way `ssh-keygen -L` does,
+  /// except that validity times are shown in UTC instead of local time
+  pub fn describe(&self, hash: FingerprintHash) -> String {
+    let mut s = String::new();
+    let list = |s: &mut String, title: &str, v: &[String]| {
+      if v.is_empty() {
+        let _ = writeln!(s, "{}: (none)", title);
+      } else {
+        let _ = writeln!(s, "{}:", title);
+        for i in v {
+          let _ = writeln!(s, "        {}", i);
+        }
+      }
+    };
+
+    let cert_type = match self.cert_type {
+      CertType::User => "user",
+      CertType::Host => "host",
+    };
+    let valid = match (self.valid_after, self.valid_before) {
+      (0, u64::MAX) => "forever".to_string(),
+      (0, b) => format!("before {}", format_time(b)),
+      (a, u64::MAX) => format!("after {}", format_time(a)),
+      (a, b) => format!("from {} to {}", format_time(a), format_time(b)),
+    };
+
+    let _ = writeln!(
+      s,
+      "Type: {} {} certificate",
+      self.key.key_type.cert_name(),
+      cert_type
+    );
+    let _ = writeln!(
+      s,
+      "Public key: {} {}",
+      self.short_name(),
+      self.key.fingerprint(hash)
+    );
+    let _ = writeln!(
+      s,
+      "Signing CA: {} {} (using {})",
+      self.ca.key_type.short_name(),
+      self.ca.fingerprint(hash),
+      self.signature_type
+    );
+    let _ = writeln!(s, "Key ID: \"{}\"", self.key_id);
+    let _ = writeln!(s, "Serial: {}", self.serial);
+    let _ = writeln!(s, "Valid: {}", valid);
+    list(&mut s, "Principals", &self.principals);
+    list(&mut s, "Critical Options", &self.critical_options);
+    list(&mut s, "Extensions", &self.extensions);
+
+    s
+  }
+}
+
+/// Parse `-cert.pub` line (`type base64 [comment]`)
+pub fn parse_line(line: &str) -> Result<Certificate> {
+  let (name, rest) = split_token(line.trim());
+  let (b64, comment) = split_token(rest);
+
+  let cert = Certificate::from_blob(&decode_base64(b64)?, comment.trim())?;
+  if cert.key.key_type.cert_name() != name {
+    return super::_raise(format!(
+      "Certificate type mismatch: {} vs {}",
+      name,
+      cert.key.key_type.cert_name()
+    ));
+  }
+
+  Ok(cert)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // certificates are produced by `ssh-keygen -s` (OpenSSH 9.2)
+
+  const HOST_CERT: &str = "ssh-ed25519-cert-v01@openssh.com AAAAIHNzaC1lZDI1NTE5LWNlcnQtdjAxQG9\
+    wZW5zc2guY29tAAAAIJ4k4dm+ADSkrVV6+JYOMmypIMLa7kpu0YB50dIRnX0CAAAAIKGsuoPPmDU+2fNnrCD21qPnt3D13rGJab\
+    9TZfPzPuAOAAAAAAAAACoAAAACAAAAB2hvc3QtaWQAAAAnAAAAEGhvc3QuZXhhbXBsZS5jb20AAAAPYWx0LmV4YW1wbGUuY29tA\
+    AAAAGWSAIAAAAAAZ3SFgAAAAAAAAAAAAAAAAAAAARcAAAAHc3NoLXJzYQAAAAMBAAEAAAEBAMuLQCgk3ClQMq3gklqS8A1yg+M5\
+    pni9fX/kv6iicgXosF5Opz45K/aG8rg8n0oJDw48sntVQIMpru3MHqJiPA41AfVKETRP+LR13kkrtjzNV8FM0NHdoMLAL3Pmg+N\
+    LyVXGx4mi9ymN7dQNfSFxjdZ4hRNnK8zbUob4tKWB/yo/OlEulDm/jCbDT+npHQYkwaQBJpWuF371iL4sh9ozpXGATE6/I8Oe8aY\
+    liK3dEL+okIoE/eyak4OaCu8cnoYcSsW6s4WiAxScuUmOjE6j0gcmiV/OdpA+df18wfXTFhXtjPGDb2p45ZA/8xcUFGQVYQYdkhX\
+    XIglmdSDxyDmAhR8AAAEUAAAADHJzYS1zaGEyLTUxMgAAAQDFPkX2bOuW/awsyA5diM9OwcIvfVS+EYYcn93tLRAPF0/fivB3nlO\
+    4gV8QjrLBLIR1A1Bqht9NaJa2EY/1JfIcQHkseCA9rBQCJKJczQABowqcn5Rq6axeGmVpsE5dHnzY+cPnN3kEpYFVwy6L5WGMkF\
+    SJYVYUicdW7F4NBf9q9PXJw8lUNO3B7Bh6t/3Bg23JMent3ktiuEdjmMU6kjAaim+AKipO2zBAycXXRaXgW8xUMwEHj54W+ktDIK\
+    ih6cywW1++VdxWJj6WR7QfowRCFgDhJx3fniQdanJqBk5f74YkUXsWICMEb/+phfrpYVVcOU0jrGX/J0bMlj1uJEPw alice@exa\
+    mple.com";
+
+  const USER_CERT: &str = "ecdsa-sha2-nistp384-cert-v01@openssh.com AAAAKGVjZHNhLXNoYTItbmlzdHAz\
+    ODQtY2VydC12MDFAb3BlbnNzaC5jb20AAAAgeV+W3dzQRyy3hrCvgJMFzrnnMeE3P43rfDYXqm3mABoAAAAIbmlzdHAzODQAAABh\
+    BDqEXGaaCsjDfYNQI7P1d0+c2b3H4e2eXbPrxWrr1DndQHg7RGJ2g102/jIk8JC9qGEcAaCBHwynJYnvq5kk2/gLJ/I/Ivxdhudq\
+    3o8WhDahxvuvzBwS5YP2ond03A1PcQAAAAAAAAAAAAAAAQAAAAR1c2VyAAAAAAAAAAAAAAAA//////////8AAAAAAAAAggAAABVw\
+    ZXJtaXQtWDExLWZvcndhcmRpbmcAAAAAAAAAF3Blcm1pdC1hZ2VudC1mb3J3YXJkaW5nAAAAAAAAABZwZXJtaXQtcG9ydC1mb3J3\
+    YXJkaW5nAAAAAAAAAApwZXJtaXQtcHR5AAAAAAAAAA5wZXJtaXQtdXNlci1yYwAAAAAAAAAAAAAAiAAAABNlY2RzYS1zaGEyLW5p\
+    c3RwMzg0AAAACG5pc3RwMzg0AAAAYQQ6hFxmmgrIw32DUCOz9XdPnNm9x+Htnl2z68Vq69Q53UB4O0RidoNdNv4yJPCQvahhHAGg\
+    gR8MpyWJ76uZJNv4CyfyPyL8XYbnat6PFoQ2ocb7r8wcEuWD9qJ3dNwNT3EAAACEAAAAE2VjZHNhLXNoYTItbmlzdHAzODQAAABp\
+    AAAAMFrTtwX8Oz8wBSqHsKqPYPp48vQm8SoCmAlOdQ2Kpd44ePoxeX6MHbpKc6dUapRV9AAAADEAnu9E8d6uDmosARyargV69+9W\
+    7HeK+3Emv2y9ctCqfOaGUzMjyOdxFI8pNwTuSFQU ec.pub";
+
+  const OPTIONS_CERT: &str = "ssh-ed25519-cert-v01@openssh.com AAAAIHNzaC1lZDI1NTE5LWNlcnQtdjAxQG9wZW\
+    5zc2guY29tAAAAIGMXHyXe55PK1TxBOAVRxOktE2nd5F4WxcoU8fFapPYQAAAAIF+IJ8dlJQWZOdjvIhZzrSVzFQ3MGH8NaekrB\
+    82dEOwwAAAAAAAAAAcAAAABAAAAB29wdHMtaWQAAAAHAAAAA2JvYgAAAAAAAAAA//////////8AAABSAAAADWZvcmNlLWNvbW1h\
+    bmQAAAANAAAACS9iaW4vdHJ1ZQAAAA5zb3VyY2UtYWRkcmVzcwAAABoAAAAWMTAuMC4wLjAvOCwxOTIuMTY4LjEuMQAAABIAAAA\
+    KcGVybWl0LXB0eQAAAAAAAAAAAAAAMwAAAAtzc2gtZWQyNTUxOQAAACDjo/cqZ3PUHE68NZC1sWJPYix4xaR2HGMT1ftajc2ntA\
+    AAAFMAAAALc3NoLWVkMjU1MTkAAABAOqozYGL7tkQhM1QRUZYfmh+CIoVoSw9ksO0R/ym2xDR7Kj0zvzLycaC0S5V7VSmlaIQot\
+    ULu+qcrXwEvPEPXAg== bob@example.com";
+
+  #[test]
+  fn test_host_cert() {
+    assert!(is_cert(HOST_CERT));
+    let cert = parse_line(HOST_CERT).unwrap();
+
+    let ref_desc = "\
+Type: ssh-ed25519-cert-v01@openssh.com host certificate
+Public key: ED25519-CERT SHA256:zFePOJlZopCjR1wruE1Rrfowzd+4hXBEsSpg6qkBl2Y
+Signing CA: RSA SHA256:mHkK6Vq63R588jv9Q79GFH0gR8t+icKjY5V+523unBQ (using rsa-sha2-512)
+Key ID: \"host-id\"
+Serial: 42
+Valid: from 2024-01-01T00:00:00 UTC to 2025-01-01T00:00:00 UTC
+Principals:
+        host.example.com
+        alt.example.com
+Critical Options: (none)
+Extensions: (none)
+";
+
+    assert_eq!(cert.describe(FingerprintHash::Sha256), ref_desc);
+    assert_eq!(cert.comment, "alice@example.com");
+
+    let (key, ca) = cert.arts(FingerprintHash::Sha256);
+    assert_eq!(
+      key.to_string(),
+      "256 SHA256:zFePOJlZopCjR1wruE1Rrfowzd+4hXBEsSpg6qkBl2Y alice@example.com (ED25519-CERT)"
+    );
+    assert_eq!(ca.labels.key_type, "RSA");
+    assert_eq!(ca.labels.bits, 2048);
+  }
+
+  #[test]
+  fn test_user_cert() {
+    let cert = parse_line(USER_CERT).unwrap();
+
+    assert_eq!(cert.cert_type, CertType::User);
+    assert_eq!(cert.key.key_type, KeyType::EcdsaP384);
+    assert_eq!(cert.extensions.len(), 5);
+    assert!(cert
+      .describe(FingerprintHash::Sha256)
+      .contains("Valid: forever\n"));
+
+    let art = cert.draw(FingerprintHash::Sha256);
+    let first = art.lines().next().unwrap();
+    assert_eq!(first, "+------[key]------+  +------[CA]-------+");
+    assert_eq!(art.lines().count(), 11);
+  }
+
+  #[test]
+  fn test_critical_options() {
+    let cert = parse_line(OPTIONS_CERT).unwrap();
+
+    assert_eq!(
+      cert.critical_options,
+      [
+        "force-command /bin/true",
+        "source-address 10.0.0.0/8,192.168.1.1"
+      ]
+    );
+    assert_eq!(cert.extensions, ["permit-pty"]);
+    assert!(cert.describe(FingerprintHash::Sha256).ends_with(
+      "\
+Critical Options:
+        force-command /bin/true
+        source-address 10.0.0.0/8,192.168.1.1
+Extensions:
+        permit-pty
+"
+    ));
+  }
+
+  #[test]
+  fn test_not_cert() {
+    let line = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKGsuoPPmDU+2fNnrCD21qPnt3D13rGJab9TZfPzPuAO";
+    assert!(!is_cert(line));
+    assert!(parse_line(line).is_err());
+  }
+
+  #[test]
+  fn test_format_time() {
+    assert_eq!(format_time(0), "1970-01-01T00:00:00 UTC");
+    assert_eq!(format_time(1_709_210_096), "2024-02-29T12:34:56 UTC");
+  }
+}
@@ -0,0 +1,306 @@
+use super::{parse_key_text, split_token, FingerprintHash, KeyArt, PublicKey, SkippedLine};
+use crate::errors::{Error, Result};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use std::io::BufRead;
+
+const HASH_MAGIC: &str = "|1|";
+
+/// Entry marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+  /// `@cert-authority`
+  CertAuthority,
+  /// `@revoked`
+  Revoked,
+}
+
+impl Marker {
+  /// Marker as written in known_hosts file
+  pub fn name(self) -> &'static str {
+    match self {
+      Marker::CertAuthority => "@cert-authority",
+      Marker::Revoked => "@revoked",
+    }
+  }
+}
+
+/// Host part of the entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hosts {
+  /// Comma-separated host patterns
+  Plain(Vec<String>),
+  /// Hashed host name (`|1|salt|hash`), see `HashKnownHosts` in ssh_config(5)
+  Hashed { salt: Vec<u8>, hash: Vec<u8> },
+}
+
+/// Parsed known_hosts entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownHost {
+  pub marker: Option<Marker>,
+  pub hosts: Hosts,
+  pub key: PublicKey,
+  /// Line number in the file, starting from 1
+  pub line: usize,
+}
+
+/// Glob match with `*` and `?` wildcards
+fn wildcard_match(pat: &[u8], s: &[u8]) -> bool {
+  match (pat.first(), s.first()) {
+    (None, None) => true,
+    (Some(b'*'), _) => {
+      wildcard_match(&pat[1..], s) || (!s.is_empty() && wildcard_match(pat, &s[1..]))
+    }
+    (Some(b'?'), Some(_)) => wildcard_match(&pat[1..], &s[1..]),
+    (Some(p), Some(c)) if p == c => wildcard_match(&pat[1..], &s[1..]),
+    _ => false,
+  }
+}
+
+impl Hosts {
+  fn parse(s: &str) -> Result<Hosts> {
+    if let Some(h) = s.strip_prefix(HASH_MAGIC) {
+      let mut parts = h.splitn(2, '|');
+      let mut b64 = || match parts.next().map(|p| STANDARD.decode(p)) {
+        Some(Ok(b)) => Ok(b),
+        _ => Err(Error::SshKey(format!("Bad hashed host: {}", s))),
+      };
+      Ok(Hosts::Hashed {
+        salt: b64()?,
+        hash: b64()?,
+      })
+    } else {
+      Ok(Hosts::Plain(s.split(',').map(String::from).collect()))
+    }
+  }
+
+  /// Check if host name matches the entry
+  ///
+  /// For non-standard ports host should be passed as `[host]:port`
+  pub fn matches(&self, host: &str) -> bool {
+    let host = host.to_lowercase();
+
+    match self {
+      Hosts::Hashed { salt, hash } => {
+        let mut mac = match Hmac::<Sha1>::new_from_slice(salt) {
+          Ok(m) => m,
+          Err(_) => return false,
+        };
+        mac.update(host.as_bytes());
+        mac.verify_slice(hash).is_ok()
+      }
+      Hosts::Plain(pats) => {
+        let mut matched = false;
+        for p in pats {
+          let p = p.to_lowercase();
+          let (negated, p) = match p.strip_prefix('!') {
+            Some(p) => (true, p),
+            None => (false, p.as_str()),
+          };
+          if wildcard_match(p.as_bytes(), host.as_bytes()) {
+            if negated {
+              return false;
+            }
+            matched = true;
+          }
+        }
+        matched
+      }
+    }
+  }
+
+  /// Host label: comma-separated patterns or `(hashed)`
+  pub fn label(&self) -> String {
+    match self {
+      Hosts::Plain(p) => p.join(","),
+      Hosts::Hashed { .. } => "(hashed)".to_string(),
+    }
+  }
+}
+
+impl KnownHost {
+  /// Check if host name matches the entry (see [`Hosts::matches()`])
+  ///
+  /// [`Hosts::matches()`]: ./enum.Hosts.html#method.matches
+  pub fn matches(&self, host: &str) -> bool {
+    self.hosts.matches(host)
+  }
+
+  /// Host label followed by marker, if any
+  pub fn label(&self) -> String {
+    self.with_marker(self.hosts.label())
+  }
+
+  fn with_marker(&self, hosts: String) -> String {
+    match self.marker {
+      Some(m) => format!("{} {}", hosts, m.name()),
+      None => hosts,
+    }
+  }
+
+  /// Label for the entry found by `host` (see [`find()`]):
+  /// hashed host names are replaced with `host` itself
+  ///
+  /// [`find()`]: ./fn.find.html
+  pub fn label_as(&self, host: &str) -> String {
+    match self.hosts {
+      Hosts::Plain(_) => self.label(),
+      Hosts::Hashed { .. } => self.with_marker(host.to_string()),
+    }
+  }
+
+  /// Visualize key fingerprint on field of default size
+  pub fn art(&self, hash: FingerprintHash) -> KeyArt {
+    self.key.art(hash)
+  }
+}
+
+/// Parse single known_hosts line
+///
+/// Line has form `[marker] hosts type base64 [comment]`
+pub fn parse_line(line: &str) -> Result<KnownHost> {
+  let (mut hosts, mut rest) = split_token(line);
+
+  let marker = match hosts {
+    "@cert-authority" => Some(Marker::CertAuthority),
+    "@revoked" => Some(Marker::Revoked),
+    m if m.starts_with('@') => return Err(Error::SshKey(format!("Unknown marker: {}", m))),
+    _ => None,
+  };
+
+  if marker.is_some() {
+    let (h, r) = split_token(rest);
+    hosts = h;
+    rest = r;
+  }
+
+  Ok(KnownHost {
+    marker,
+    hosts: Hosts::parse(hosts)?,
+    key: parse_key_text(rest)?,
+    line: 0,
+  })
+}
+
+/// Parse all entries from known_hosts file
+///
+/// Empty lines and comments (`#`) are skipped. Like ssh, lines that can't
+/// be parsed (unknown marker, unsupported key, bad hashed host) are skipped
+/// too and returned separately, only read errors fail the whole file
+pub fn parse<R: BufRead>(r: R) -> Result<(Vec<KnownHost>, Vec<SkippedLine>)> {
+  let mut entries = vec![];
+  let mut skipped = vec![];
+
+  for (i, line) in r.lines().enumerate() {
+    let line = line.map_err(Error::IO)?;
+    let l = line.trim();
+    if l.is_empty() || l.starts_with('#') {
+      continue;
+    }
+
+    match parse_line(l) {
+      Ok(e) => entries.push(KnownHost { line: i + 1, ..e }),
+      Err(error) => skipped.push(SkippedLine { line: i + 1, error }),
+    }
+  }
+
+  Ok((entries, skipped))
+}
+
+/// Find entries matching host name, including hashed ones
+pub fn find<'a>(entries: &'a [KnownHost], host: &'a str) -> impl Iterator<Item = &'a KnownHost> {
+  entries.iter().filter(move |e| e.matches(host))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ssh::KeyType;
+
+  // hashed entries are produced by `ssh-keygen -H` (OpenSSH 9.2)
+
+  const ED25519: &str =
+    "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKGsuoPPmDU+2fNnrCD21qPnt3D13rGJab9TZfPzPuAO";
+
+  const ECDSA: &str = "ecdsa-sha2-nistp384 AAAAE2VjZHNhLXNoYTItbmlzdHAzODQAAAAIbmlzdHAzODQAAABhBD\
+    qEXGaaCsjDfYNQI7P1d0+c2b3H4e2eXbPrxWrr1DndQHg7RGJ2g102/jIk8JC9qGEcAaCBHwynJYnvq5kk2/gLJ/I/Ivxdhudq3\
+    o8WhDahxvuvzBwS5YP2ond03A1PcQ==";
+
+  fn known_hosts() -> String {
+    [
+      "# comment".to_string(),
+      format!("example.com,10.0.0.1 {}", ED25519),
+      format!(
+        "|1|8KGa82r05X5jE7dGgAUXqOR0Hlk=|/sGk1UUQJqg0fZSVbXsAaQwtHQ4= {}",
+        ED25519
+      ),
+      format!(
+        "|1|kdtLFgjTzSS3Pm09vQUFjbsmYD4=|uB8uYcGCM2n+FaR7XYAR1tR1X+g= {}",
+        ED25519
+      ),
+      format!(
+        "|1|ZysV1nn9cxwfYBqBS89DW2Srd3k=|ZtfOwA8n1fJZGzcCaHUyuhcDQZ8= {}",
+        ECDSA
+      ),
+      format!(
+        "@cert-authority *.example.com,!bad.example.com {} ca key",
+        ED25519
+      ),
+      format!("@revoked [git.example.com]:2222 {}", ECDSA),
+    ]
+    .join("\n")
+  }
+
+  fn lines(entries: &[KnownHost], host: &str) -> Vec<usize> {
+    find(entries, host).map(|e| e.line).collect()
+  }
+
+  #[test]
+  fn test_parse() {
+    let (entries, skipped) = parse(known_hosts().as_bytes()).unwrap();
+    assert_eq!(entries.len(), 6);
+    assert!(skipped.is_empty());
+
+    assert_eq!(entries[0].label(), "example.com,10.0.0.1");
+    assert_eq!(entries[1].label(), "(hashed)");
+    assert_eq!(entries[3].key.key_type, KeyType::EcdsaP384);
+    assert_eq!(entries[4].marker, Some(Marker::CertAuthority));
+    assert_eq!(entries[4].key.comment, "ca key");
+    assert_eq!(entries[5].label(), "[git.example.com]:2222 @revoked");
+    assert_eq!(entries[1].label_as("example.com"), "example.com");
+    assert_eq!(entries[0].label_as("example.com"), "example.com,10.0.0.1");
+  }
+
+  #[test]
+  fn test_find() {
+    let (entries, _) = parse(known_hosts().as_bytes()).unwrap();
+
+    assert_eq!(lines(&entries, "example.com"), [2, 3]);
+    assert_eq!(lines(&entries, "EXAMPLE.com"), [2, 3]);
+    assert_eq!(lines(&entries, "10.0.0.1"), [2, 4]);
+    assert_eq!(lines(&entries, "[git.example.com]:2222"), [5, 7]);
+    assert_eq!(lines(&entries, "www.example.com"), [6]);
+    assert_eq!(lines(&entries, "bad.example.com"), Vec::<usize>::new());
+  }
+
+  #[test]
+  fn test_bad_lines() {
+    assert!(parse_line(&format!("@bogus host {}", ED25519)).is_err());
+    assert!(parse_line(&format!("|1|???|??? {}", ED25519)).is_err());
+
+    let file = format!(
+      "\nhost {k}\nhost ssh-ed25519 AAAA\n@bogus host {k}\n|1|???|??? {k}\nother {k}\n",
+      k = ED25519
+    );
+    let (entries, skipped) = parse(file.as_bytes()).unwrap();
+    let lines: Vec<_> = entries.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [2, 6]);
+    let lines: Vec<_> = skipped.iter().map(|s| s.line).collect();
+    assert_eq!(lines, [3, 4, 5]);
+    assert!(skipped[1].to_string().starts_with("line 4: "));
+  }
+}
@@ -0,0 +1,388 @@
+use crate::bishop_art::{BishopResult, DrawingOptions, FrameStyle, Layout};
+use crate::color::{Color, ColorDepth, ColorOptions, Part, Style};
+
+use std::fmt::Write;
+use unicode_width::UnicodeWidthStr;
+
+/// Glyph cell width relative to font size, usual for monospace fonts
+const GLYPH_ASPECT: f64 = 0.6;
+
+/// How field cells are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvgMode {
+  /// Chars from char list in monospace `<text>` grid, same as text output
+  Glyphs,
+  /// Filled squares colored by visit count
+  Squares,
+}
+
+/// Options for SVG drawing
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+  pub mode: SvgMode,
+
+  /// Line height in glyph mode or square size in squares mode, in pixels
+  pub cell_size: u32,
+
+  pub background: Color,
+
+  /// Color of glyphs and frame if [`color`] is not set in [`DrawingOptions`]
+  ///
+  /// [`color`]: ../bishop_art/struct.DrawingOptions.html#structfield.color
+  /// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+  pub foreground: Color,
+}
+
+impl Default for SvgOptions {
+  fn default() -> Self {
+    SvgOptions {
+      mode: SvgMode::Glyphs,
+      cell_size: 16,
+      background: Color::Rgb(255, 255, 255),
+      foreground: Color::Rgb(0, 0, 0),
+    }
+  }
+}
+
+/// Round coordinate to avoid float noise like `182.39999999999998`
+fn px(v: f64) -> f64 {
+  (v * 100.0).round() / 100.0
+}
+
+fn escape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&apos;"),
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+/// `fill` and `font-weight` attributes for style
+fn style_attrs(st: Option<Style>, fg: Color) -> String {
+  let st = st.unwrap_or_default();
+  let mut a = format!(" fill=\"{}\"", st.color.unwrap_or(fg).hex());
+  if st.bold {
+    a.push_str(" font-weight=\"bold\"");
+  }
+  a
+}
+
+fn header(out: &mut String, w: f64, h: f64, bg: Color) {
+  let _ = writeln!(
+    out,
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+    w = w,
+    h = h
+  );
+  let _ = writeln!(
+    out,
+    "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+    bg.hex()
+  );
+}
+
+/// Field and frame as rows of monospace text
+fn draw_glyphs(res: &BishopResult, o: &DrawingOptions, s: &SvgOptions) -> String {
+  let lines = res.lines(o);
+  let size = f64::from(s.cell_size);
+  let cols = lines
+    .iter()
+    .map(|l| l.iter().map(|&(_, c)| c).collect::<String>().width())
+    .max()
+    .unwrap_or(0);
+  let line_w = px(cols as f64 * size * GLYPH_ASPECT);
+
+  let mut out = String::new();
+  header(&mut out, line_w, lines.len() as f64 * size, s.background);
+  let _ = writeln!(
+    out,
+    "<g font-family=\"monospace\" font-size=\"{}\" xml:space=\"preserve\">",
+    size
+  );
+
+  let style = |p: Part| o.color.as_ref().and_then(|c| c.style(p));
+
+  for (y, line) in lines.iter().enumerate() {
+    let _ = write!(
+      out,
+      "<text x=\"0\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\"{}>",
+      px((y as f64 + 0.8) * size),
+      line_w,
+      style_attrs(style(Part::Frame), s.foreground)
+    );
+
+    for &(part, c) in line {
+      let c = escape(&c.to_string());
+      match (part, style(part)) {
+        (Part::Background, _) | (Part::Frame, _) | (_, None) => out.push_str(&c),
+        (_, st) => {
+          let _ = write!(out, "<tspan{}>{}</tspan>", style_attrs(st, s.foreground), c);
+        }
+      }
+    }
+
+    out.push_str("</text>\n");
+  }
+
+  out.push_str("</g>\n</svg>\n");
+  out
+}
+
+/// Field as colored squares inside rectangular frame with labels,
+/// odd rows are shifted by half a square in hex layout
+fn draw_squares(res: &BishopResult, o: &DrawingOptions, s: &SvgOptions) -> String {
+  let (w, h) = (res.width(), res.height());
+  let size = f64::from(s.cell_size);
+  let hex = res.layout() == Layout::Hex;
+  let shift = |y: usize| if hex && y % 2 == 1 { size / 2.0 } else { 0.0 };
+  let extra = shift(1);
+  let default_colors;
+  let colors = match &o.color {
+    Some(c) => c,
+    None => {
+      default_colors = ColorOptions::with_depth(ColorDepth::TrueColor);
+      &default_colors
+    }
+  };
+
+  // labels are placed like in text output, in columns of font width
+  // between the corners, and drawn over the frame line without brackets;
+  // wrapped ones take rows of their own above and below the frame
+  let font = px(size * 0.8);
+  let col_w = font * GLYPH_ASPECT;
+  let (inner, full) = (w as f64 * size + extra, (w + 2) as f64 * size + extra);
+  let (cols, full_cols) = ((inner / col_w) as usize, (full / col_w) as usize);
+  let plain = FrameStyle {
+    chars: None,
+    label_open: String::new(),
+    label_close: String::new(),
+    label_padding: 0,
+    overflow: o.frame.overflow,
+  };
+  let (top, bottom) = (o.labels(true), o.labels(false));
+  let (top_wrap, bottom_wrap) = (
+    plain.wrapped(cols, &top, full_cols),
+    plain.wrapped(cols, &bottom, full_cols),
+  );
+  let wrap_h = (top_wrap.len() + bottom_wrap.len()) as f64 * size;
+
+  let mut out = String::new();
+  header(&mut out, full, (h + 2) as f64 * size + wrap_h, s.background);
+
+  let frame = colors.frame.color.unwrap_or(s.foreground).hex();
+  let text = |out: &mut String, text: &str, ln: usize, x: f64, y: f64| {
+    let lw = px((ln + 1) as f64 * col_w);
+    let _ = writeln!(
+      out,
+      "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+      px(x - lw / 2.0),
+      px(y - size / 2.0),
+      lw,
+      size,
+      s.background.hex()
+    );
+    let _ = writeln!(
+      out,
+      "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" \
+       text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>",
+      px(x),
+      px(y),
+      font,
+      frame,
+      escape(text)
+    );
+  };
+  let wrapped = |out: &mut String, lines: &[(usize, String, usize)], y0: f64| {
+    let x0 = (full - full_cols as f64 * col_w) / 2.0;
+    for (i, (col, line, ln)) in lines.iter().enumerate() {
+      let x = x0 + (*col as f64 + *ln as f64 / 2.0) * col_w;
+      text(out, line, *ln, x, y0 + (i as f64 + 0.5) * size);
+    }
+  };
+
+  wrapped(&mut out, &top_wrap, 0.0);
+  if !top_wrap.is_empty() {
+    let _ = writeln!(
+      out,
+      "<g transform=\"translate(0 {})\">",
+      top_wrap.len() as f64 * size
+    );
+  }
+
+  for y in 0..h {
+    for x in 0..w {
+      let part = BishopResult::part(*res.field().get(x, y));
+      if let Some(c) = colors.style(part).and_then(|st| st.color) {
+        let _ = writeln!(
+          out,
+          "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\"/>",
+          (x + 1) as f64 * size + shift(y),
+          (y + 1) as f64 * size,
+          c.hex(),
+          s = size
+        );
+      }
+    }
+  }
+
+  if o.frame.chars.is_some() {
+    let _ = writeln!(
+      out,
+      "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+      size / 2.0,
+      size / 2.0,
+      (w + 1) as f64 * size + extra,
+      (h + 1) as f64 * size,
+      frame,
+      size / 8.0
+    );
+  }
+
+  let x0 = size + (inner - cols as f64 * col_w) / 2.0;
+  for (labels, y) in [(&top, size / 2.0), (&bottom, (h as f64 + 1.5) * size)] {
+    for (col, label, ln) in plain.place(cols, labels).0 {
+      let x = x0 + (col as f64 + ln as f64 / 2.0) * col_w;
+      text(&mut out, &label, ln, x, y);
+    }
+  }
+
+  // captions go down the side frame lines, one cluster per row
+  let right_x = (w as f64 + 1.5) * size + extra;
+  for (cap, x) in [(&o.left_caption, size / 2.0), (&o.right_caption, right_x)] {
+    if let Some((start, cells)) = plain.caption(cap, h) {
+      for (i, c) in cells.into_iter().enumerate() {
+        let y = (start + i) as f64 * size + size * 1.5;
+        text(&mut out, c, 1, x, y);
+      }
+    }
+  }
+
+  wrapped(&mut out, &bottom_wrap, (h + 2) as f64 * size);
+  if !top_wrap.is_empty() {
+    out.push_str("</g>\n");
+  }
+
+  out.push_str("</svg>\n");
+  out
+}
+
+/// Draw field as standalone SVG document
+///
+/// Frame texts and char list are taken from [`DrawingOptions`],
+/// its [`color`] is used for glyphs, squares and frame
+/// (squares mode falls back to 24-bit palette if it's not set)
+///
+/// [`DrawingOptions`]: ../bishop_art/struct.DrawingOptions.html
+/// [`color`]: ../bishop_art/struct.DrawingOptions.html#structfield.color
+pub fn draw(res: &BishopResult, o: &DrawingOptions, s: &SvgOptions) -> String {
+  match s.mode {
+    SvgMode::Glyphs => draw_glyphs(res, o, s),
+    SvgMode::Squares => draw_squares(res, o, s),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bishop_art::{Align, BishopArt, Label, Overflow};
+
+  #[test]
+  fn test_glyphs() {
+    let res = BishopArt::new().chain(b"svg").result();
+    let o = DrawingOptions {
+      top_text: "a<b".to_string(),
+      ..Default::default()
+    };
+    let svg = draw(&res, &o, &SvgOptions::default());
+
+    assert!(
+      svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"182.4\" height=\"176\"")
+    );
+    assert!(svg.ends_with("</svg>\n"));
+    assert!(svg.contains(">+------[a&lt;b]------+</text>"));
+
+    // text rows match text output
+    let text: Vec<_> = res.draw_with_opts(&o).lines().map(escape).collect();
+    let rows: Vec<_> = svg
+      .lines()
+      .filter_map(|l| l.strip_prefix("<text "))
+      .map(|l| &l[l.find('>').unwrap() + 1..l.len() - "</text>".len()])
+      .collect();
+    assert_eq!(rows, text);
+
+    let colored = draw(
+      &res,
+      &DrawingOptions {
+        color: Some(ColorOptions::default()),
+        ..o
+      },
+      &SvgOptions::default(),
+    );
+    assert!(colored.contains("<tspan fill=\"#ffffff\" font-weight=\"bold\">S</tspan>"));
+  }
+
+  #[test]
+  fn test_squares() {
+    let res = BishopArt::new().chain(b"svg").result();
+    let s = SvgOptions {
+      mode: SvgMode::Squares,
+      cell_size: 10,
+      ..Default::default()
+    };
+    let svg = draw(&res, &DrawingOptions::default(), &s);
+
+    let visited = res.field().vec.iter().filter(|&&v| v != 0).count();
+    let squares = svg.matches("<rect x=").count() - 1; // w/o frame
+    assert_eq!(squares, visited);
+    assert!(svg.contains("width=\"190\" height=\"110\""));
+    assert!(!svg.contains("<text"));
+
+    // labels are placed as in text output, captions go char by char
+    let o = DrawingOptions {
+      top_text: "top".into(),
+      bottom_labels: vec![
+        Label::new("left", Align::Left),
+        Label::new("right", Align::Right),
+      ],
+      left_caption: "ab".into(),
+      ..Default::default()
+    };
+    let svg = draw(&res, &o, &s);
+    let texts: Vec<_> = svg
+      .lines()
+      .filter_map(|l| l.strip_prefix("<text x=\""))
+      .map(|l| {
+        let x: f64 = l[..l.find('"').unwrap()].parse().unwrap();
+        (x, &l[l.find('>').unwrap() + 1..l.len() - "</text>".len()])
+      })
+      .collect();
+    assert_eq!(texts.len(), 5);
+    assert_eq!(texts[0].1, "top");
+    assert!((texts[0].0 - 95.0).abs() < 3.0);
+    assert_eq!(texts[1].1, "left");
+    assert_eq!(texts[2].1, "right");
+    assert!(texts[1].0 < 40.0 && texts[2].0 > 150.0);
+    assert_eq!(&texts[3..], &[(5.0, "a"), (5.0, "b")]);
+
+    // wrapped labels add rows above and below the frame
+    let o = DrawingOptions {
+      top_text: "top ".repeat(20),
+      bottom_text: "bottom".into(),
+      frame: FrameStyle {
+        overflow: Overflow::Wrap,
+        ..FrameStyle::ascii()
+      },
+      ..Default::default()
+    };
+    let svg = draw(&res, &o, &s);
+    assert!(svg.contains("width=\"190\" height=\"130\""));
+    assert!(svg.contains("<g transform=\"translate(0 20)\">"));
+  }
+}
@@ -1,6 +1,8 @@
 use std::ops::{Index, IndexMut};
+use std::slice::Chunks;
 
-#[derive(PartialEq, Debug)]
+/// Fixed size 2D vector stored row by row
+#[derive(PartialEq, Debug, Clone)]
 pub struct Vec2D<T> {
   pub vec: Vec<T>,
   pub w: usize,
@@ -8,8 +10,37 @@ pub struct Vec2D<T> {
 }
 
 impl<T: Clone> Vec2D<T> {
+  /// Create `w`x`h` vector filled with `init`
   pub fn new(w: usize, h: usize, init: T) -> Vec2D<T> {
-    Vec2D { vec: vec![init; w * h], w, h }
+    Vec2D {
+      vec: vec![init; w * h],
+      w,
+      h,
+    }
+  }
+
+  /// Iterate over rows from top to bottom
+  pub fn rows(&self) -> Chunks<'_, T> {
+    self.vec.chunks(self.w.max(1))
+  }
+
+  /// Iterate over `((x, y), value)` row by row
+  pub fn iter_xy(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+    let w = self.w;
+    self
+      .vec
+      .iter()
+      .enumerate()
+      .map(move |(i, v)| ((i % w, i / w), v))
+  }
+
+  /// Create vector of the same size with `f` applied to each value
+  pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Vec2D<U> {
+    Vec2D {
+      vec: self.vec.iter().map(f).collect(),
+      w: self.w,
+      h: self.h,
+    }
   }
 
   #[inline(always)]